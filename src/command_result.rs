@@ -1,10 +1,12 @@
 //! CommandResult - Encapsulates the result of executing a command
 
+use crate::{delta::Delta, segments::Segments};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Type of operation performed
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Operation {
     Action,
@@ -12,6 +14,10 @@ pub enum Operation {
     Save,
     Restore,
     Score,
+    Inventory,
+    Look,
+    Restart,
+    Hint,
     Quit,
     Error,
 }
@@ -24,21 +30,160 @@ impl std::fmt::Display for Operation {
             Operation::Save => write!(f, "save"),
             Operation::Restore => write!(f, "restore"),
             Operation::Score => write!(f, "score"),
+            Operation::Inventory => write!(f, "inventory"),
+            Operation::Look => write!(f, "look"),
+            Operation::Restart => write!(f, "restart"),
+            Operation::Hint => write!(f, "hint"),
             Operation::Quit => write!(f, "quit"),
             Operation::Error => write!(f, "error"),
         }
     }
 }
 
+/// Why the game rejected a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The parser did not recognize the verb
+    UnknownVerb,
+    /// The parser did not recognize a noun
+    UnknownNoun,
+    /// The object is not visible or not present
+    NotVisible,
+    /// The command was understood but cannot be done
+    Impossible,
+    /// The command is missing a noun or is empty
+    Incomplete,
+    /// The parser is asking which object was meant
+    Disambiguation,
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureKind::UnknownVerb => write!(f, "unknown_verb"),
+            FailureKind::UnknownNoun => write!(f, "unknown_noun"),
+            FailureKind::NotVisible => write!(f, "not_visible"),
+            FailureKind::Impossible => write!(f, "impossible"),
+            FailureKind::Incomplete => write!(f, "incomplete"),
+            FailureKind::Disambiguation => write!(f, "disambiguation"),
+        }
+    }
+}
+
+/// Where the game stands after a command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GameState {
+    /// The game is in progress
+    #[default]
+    Playing,
+    /// The player died and the game is showing its end menu
+    Dead,
+    /// The player won and the game is showing its end menu
+    Won,
+    /// The game is over for another reason, or has been quit
+    Ended,
+}
+
+impl GameState {
+    /// Check if the game is over and waiting on restart, restore or quit
+    pub fn is_over(&self) -> bool {
+        *self != GameState::Playing
+    }
+}
+
+impl std::fmt::Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameState::Playing => write!(f, "playing"),
+            GameState::Dead => write!(f, "dead"),
+            GameState::Won => write!(f, "won"),
+            GameState::Ended => write!(f, "ended"),
+        }
+    }
+}
+
+/// How the interpreter's output for a command ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadEnd {
+    /// The output ended at the `>` prompt
+    Prompt,
+    /// The output matched another pattern being waited for, e.g. a save
+    /// confirmation or a yes/no question
+    Pattern,
+    /// Nothing matched before a read timed out
+    Timeout,
+}
+
+impl std::fmt::Display for ReadEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadEnd::Prompt => write!(f, "prompt"),
+            ReadEnd::Pattern => write!(f, "pattern"),
+            ReadEnd::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
 /// Result of executing a command
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CommandResult {
     pub input: String,
+    /// Command after normalization, when normalization changed the input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sent: Option<String>,
     pub raw_output: String,
+    /// `raw_output` split into echo, status line, story text and prompt
+    #[serde(default)]
+    pub segments: Segments,
     pub operation: Operation,
     pub success: bool,
+    /// Why the command failed, when the output matched a known failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureKind>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// Yes/no question the game is waiting on, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yes_no_question: Option<String>,
+    /// Score reported by the game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    /// Maximum possible score reported by the game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<i32>,
+    /// Number of moves reported by the game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moves: Option<i32>,
+    /// Name of the room the player is in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Time of day shown in the status line, e.g. "10:30 AM"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// What changed since the previous turn, tracked by `Session`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delta: Option<Delta>,
+    /// State of the game after the command, tracked by `Session`
+    #[serde(default)]
+    pub game_state: GameState,
+    /// Session turn number, counting every command executed
+    #[serde(default)]
+    pub turn: u64,
+    /// When the command started, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub started_at: u64,
+    /// Time taken by the command, in milliseconds
+    #[serde(default)]
+    pub elapsed_ms: u64,
+    /// How the command's output ended, if it read any
+    ///
+    /// A command that reads several times, like save, ends how its last read
+    /// ended, unless an earlier read timed out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_end: Option<ReadEnd>,
     #[serde(flatten)]
     pub details: HashMap<String, serde_json::Value>,
 }
@@ -53,11 +198,26 @@ impl CommandResult {
         message: Option<String>,
     ) -> Self {
         Self {
+            segments: Segments::split(&raw_output, &input),
             input,
+            sent: None,
             raw_output,
             operation,
             success,
+            failure: None,
             message,
+            yes_no_question: None,
+            score: None,
+            max_score: None,
+            moves: None,
+            location: None,
+            time: None,
+            delta: None,
+            game_state: GameState::default(),
+            turn: 0,
+            started_at: 0,
+            elapsed_ms: 0,
+            read_end: None,
             details: HashMap::new(),
         }
     }
@@ -72,15 +232,35 @@ impl CommandResult {
         details: HashMap<String, serde_json::Value>,
     ) -> Self {
         Self {
+            segments: Segments::split(&raw_output, &input),
             input,
+            sent: None,
             raw_output,
             operation,
             success,
+            failure: None,
             message,
+            yes_no_question: None,
+            score: None,
+            max_score: None,
+            moves: None,
+            location: None,
+            time: None,
+            delta: None,
+            game_state: GameState::default(),
+            turn: 0,
+            started_at: 0,
+            elapsed_ms: 0,
+            read_end: None,
             details,
         }
     }
 
+    /// JSON Schema describing the serialized form of a result
+    pub fn schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(CommandResult)).unwrap_or_default()
+    }
+
     /// Check if this is an action command
     pub fn is_action_command(&self) -> bool {
         self.operation == Operation::Action
@@ -101,6 +281,27 @@ impl CommandResult {
         !self.success
     }
 
+    /// Check if the game is waiting for a yes/no answer
+    pub fn is_yes_no_question(&self) -> bool {
+        self.yes_no_question.is_some()
+    }
+
+    /// Check if any of the command's reads timed out
+    pub fn is_timeout(&self) -> bool {
+        self.read_end == Some(ReadEnd::Timeout)
+    }
+
+    /// Check if the game ended with this command or before it
+    pub fn is_game_over(&self) -> bool {
+        self.game_state.is_over()
+    }
+
+    /// Put output from an earlier exchange in front of this result's output
+    pub fn prepend_output(&mut self, earlier: &str) {
+        self.raw_output = format!("{}{}", earlier, self.raw_output);
+        self.segments = Segments::split(&self.raw_output, &self.input);
+    }
+
     /// Add a detail field
     pub fn add_detail(&mut self, key: String, value: serde_json::Value) {
         self.details.insert(key, value);
@@ -1,28 +1,80 @@
 //! Commands - Game command implementations
 
 use crate::{
-    command_result::{CommandResult, Operation},
+    command_result::{CommandResult, FailureKind, Operation},
     dfrotz::Dfrotz,
+    inventory::parse_inventory,
+    language::LanguageProfile,
+    patterns::Patterns,
+    room::parse_room,
     savefile::Savefile,
-    Result, FAILURE_PATTERNS, FILENAME_PROMPT_REGEX, PROMPT_REGEX, SCORE_REGEX,
+    score::parse_score_with,
+    Result, PROMPT_REGEX, VERBATIM_PREFIX,
 };
 use regex::Regex;
 use std::collections::HashMap;
 
-/// Parse save/restore command from input
-fn parse_save_restore(input: &str, game_name: Option<&str>) -> Option<Savefile> {
-    let parts: Vec<&str> = input.split_whitespace().collect();
-    if parts.is_empty() {
-        return None;
+lazy_static::lazy_static! {
+    /// Parser question listing the objects a command could refer to
+    static ref DISAMBIGUATION_REGEX: Regex =
+        Regex::new(r"(?is)Which (?:\w+ )?do you mean,?\s+(.+?)\?").unwrap();
+}
+
+/// Parse the candidates from a "Which do you mean, the X or the Y?" question
+pub fn parse_disambiguation(output: &str) -> Option<Vec<String>> {
+    let captures = DISAMBIGUATION_REGEX.captures(output)?;
+    let list = captures[1].split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let candidates: Vec<String> = list
+        .replace(", or ", ", ")
+        .replace(" or ", ", ")
+        .split(',')
+        .map(|candidate| {
+            let candidate = candidate.trim();
+            ["the ", "a ", "an "]
+                .iter()
+                .find_map(|article| candidate.strip_prefix(article))
+                .unwrap_or(candidate)
+                .to_string()
+        })
+        .filter(|candidate| !candidate.is_empty())
+        .collect();
+
+    if candidates.is_empty() {
+        None
+    } else {
+        Some(candidates)
+    }
+}
+
+/// Prefix naming a save slot explicitly, as in `save slot=before_dragon`
+pub const SLOT_PREFIX: &str = "slot=";
+
+/// Parse the save file named by a save or restore command
+///
+/// `save` and `restore` on their own use the default slot, and
+/// `slot=<name>` names one. A bare second word is left for the game, since
+/// "save princess" or "restore power" are story commands.
+pub fn parse_save_restore(tokens: &[&str], game_name: Option<&str>) -> Option<Savefile> {
+    let game_name = game_name.map(|s| s.to_string());
+
+    match tokens {
+        [_] => Some(Savefile::new(game_name, None)),
+        [_, slot] => {
+            strip_slot_prefix(slot).map(|slot| Savefile::new(game_name, Some(slot.to_string())))
+        }
+        _ => None,
     }
+}
 
-    let slot = if parts.len() > 1 {
-        Some(parts[1].to_string())
+/// Slot name after a case-insensitive `SLOT_PREFIX`
+fn strip_slot_prefix(token: &str) -> Option<&str> {
+    let prefix = token.get(..SLOT_PREFIX.len())?;
+    if prefix.eq_ignore_ascii_case(SLOT_PREFIX) {
+        Some(&token[SLOT_PREFIX.len()..])
     } else {
         None
-    };
-
-    Some(Savefile::new(game_name.map(|s| s.to_string()), slot))
+    }
 }
 
 /// Commands factory
@@ -31,47 +83,100 @@ pub struct Commands;
 impl Commands {
     /// Create a command from user input
     pub fn create(input: &str, game_name: Option<&str>) -> Box<dyn Command> {
-        let trimmed = input.trim().to_lowercase();
-
-        match trimmed.as_str() {
-            "score" => Box::new(ScoreCommand),
-            "quit" => Box::new(QuitCommand),
-            s if s.starts_with("save") => {
-                if let Some(savefile) = parse_save_restore(input, game_name) {
-                    Box::new(SaveCommand { savefile })
-                } else {
-                    Box::new(ActionCommand {
-                        input: input.to_string(),
-                    })
-                }
+        Self::create_with(input, game_name, &[], &LanguageProfile::default())
+    }
+
+    /// Create a command from user input, sending `passthrough` verbs to the game
+    ///
+    /// System verbs are recognized in the given language. Some games use SAVE
+    /// or RESTORE as story verbs; listing them in `passthrough` stops them from
+    /// being intercepted. Input starting with `VERBATIM_PREFIX` is sent to the
+    /// game as-is, with the prefix removed, except a bare save or restore verb:
+    /// the game would prompt for a filename, so those still use the default slot.
+    pub fn create_with(
+        input: &str,
+        game_name: Option<&str>,
+        passthrough: &[String],
+        language: &LanguageProfile,
+    ) -> Box<dyn Command> {
+        let trimmed = input.trim();
+        let action = |input: &str| -> Box<dyn Command> {
+            Box::new(ActionCommand {
+                input: input.to_string(),
+            })
+        };
+
+        if let Some(verbatim) = trimmed.strip_prefix(VERBATIM_PREFIX) {
+            let verbatim = verbatim.trim_start();
+            // A bare SAVE or RESTORE would make the game ask for a filename,
+            // and the next line of input would answer it
+            let verb = verbatim.to_lowercase();
+            if language.is_save_verb(&verb) {
+                let savefile = Savefile::new(game_name.map(str::to_string), None);
+                return Box::new(SaveCommand { savefile });
             }
-            s if s.starts_with("restore") => {
-                if let Some(savefile) = parse_save_restore(input, game_name) {
-                    Box::new(RestoreCommand { savefile })
-                } else {
-                    Box::new(ActionCommand {
-                        input: input.to_string(),
-                    })
-                }
+            if language.is_restore_verb(&verb) {
+                let savefile = Savefile::new(game_name.map(str::to_string), None);
+                return Box::new(RestoreCommand { savefile });
             }
-            _ => Box::new(ActionCommand {
-                input: input.to_string(),
-            }),
+            return action(verbatim);
+        }
+
+        let lowered = trimmed.to_lowercase();
+        let tokens: Vec<&str> = lowered.split_whitespace().collect();
+        let original_tokens: Vec<&str> = trimmed.split_whitespace().collect();
+
+        let verb = match tokens.first() {
+            Some(verb) => *verb,
+            None => return action(input),
+        };
+
+        if passthrough.iter().any(|p| p.to_lowercase() == verb) {
+            return action(input);
+        }
+
+        if tokens.len() == 1 && language.is_score_verb(verb) {
+            Box::new(ScoreCommand)
+        } else if tokens.len() == 1 && language.is_inventory_verb(verb) {
+            Box::new(InventoryCommand)
+        } else if tokens.len() == 1 && language.is_look_verb(verb) {
+            Box::new(LookCommand)
+        } else if tokens.len() == 1 && language.is_restart_verb(verb) {
+            Box::new(RestartCommand)
+        } else if tokens.len() == 1 && language.is_quit_verb(verb) {
+            Box::new(QuitCommand)
+        } else if language.is_save_verb(verb) {
+            match parse_save_restore(&original_tokens, game_name) {
+                Some(savefile) => Box::new(SaveCommand { savefile }),
+                None => action(input),
+            }
+        } else if language.is_restore_verb(verb) {
+            match parse_save_restore(&original_tokens, game_name) {
+                Some(savefile) => Box::new(RestoreCommand { savefile }),
+                None => action(input),
+            }
+        } else {
+            action(input)
         }
     }
 }
 
 /// Trait for executable commands
 pub trait Command {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult>;
+    fn execute(&self, game: &mut Dfrotz, _patterns: &Patterns) -> Result<CommandResult>;
     fn input(&self) -> String;
+
+    /// Operation the command performs
+    fn operation(&self) -> Operation {
+        Operation::Action
+    }
 }
 
 /// Start command - initialize the game
 pub struct StartCommand;
 
 impl Command for StartCommand {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult> {
+    fn execute(&self, game: &mut Dfrotz, _patterns: &Patterns) -> Result<CommandResult> {
         let raw_output = game.read_until(Some(&PROMPT_REGEX))?;
 
         Ok(CommandResult::new(
@@ -86,6 +191,10 @@ impl Command for StartCommand {
     fn input(&self) -> String {
         String::new()
     }
+
+    fn operation(&self) -> Operation {
+        Operation::Start
+    }
 }
 
 /// Action command - generic game actions (look, go north, etc.)
@@ -94,19 +203,33 @@ pub struct ActionCommand {
 }
 
 impl Command for ActionCommand {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult> {
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        // Output is complete at the prompt. A yes/no question the game waits on
+        // has no prompt after it, so the read ends at the timeout; matching the
+        // question here would end reads early at narrative questions.
         game.write(&self.input)?;
         let raw_output = game.read_until(Some(&PROMPT_REGEX))?;
 
-        let success = !Self::failure_detected(&raw_output);
+        let failure = patterns.failures.classify(&raw_output);
+        let yes_no_question = patterns.language.yes_no_question(&raw_output);
 
-        Ok(CommandResult::new(
+        let mut result = CommandResult::new(
             self.input.clone(),
             raw_output,
             Operation::Action,
-            success,
+            failure.is_none(),
             None,
-        ))
+        );
+        result.failure = failure;
+        result.yes_no_question = yes_no_question;
+
+        if failure == Some(FailureKind::Disambiguation) {
+            if let Some(candidates) = parse_disambiguation(&result.raw_output) {
+                result.add_detail("candidates".to_string(), serde_json::json!(candidates));
+            }
+        }
+
+        Ok(result)
     }
 
     fn input(&self) -> String {
@@ -114,52 +237,126 @@ impl Command for ActionCommand {
     }
 }
 
-impl ActionCommand {
-    fn failure_detected(output: &str) -> bool {
-        FAILURE_PATTERNS
-            .iter()
-            .any(|pattern| pattern.is_match(output))
-    }
-}
-
 /// Score command
 pub struct ScoreCommand;
 
 impl Command for ScoreCommand {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult> {
-        game.write("score")?;
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        game.write(patterns.language.score_verb())?;
         let raw_output = game.read_until(Some(&PROMPT_REGEX))?;
 
-        let mut details = HashMap::new();
-        let mut message = None;
-
-        if let Some(captures) = SCORE_REGEX.captures(&raw_output) {
-            if let Some(score_match) = captures.get(1) {
-                let score: i32 = score_match.as_str().parse().unwrap_or(0);
-                details.insert("score".to_string(), serde_json::json!(score));
-
-                if let Some(max_match) = captures.get(2) {
-                    let max_score: i32 = max_match.as_str().parse().unwrap_or(0);
-                    details.insert("out_of".to_string(), serde_json::json!(max_score));
-                    message = Some(format!("Score: {}/{}", score, max_score));
-                } else {
-                    message = Some(format!("Score: {}", score));
-                }
-            }
-        }
+        let score = parse_score_with(&raw_output, &patterns.language.score_formats);
+        let message = score.as_ref().map(|score| match score.out_of {
+            Some(out_of) => format!("Score: {}/{}", score.score, out_of),
+            None => format!("Score: {}", score.score),
+        });
 
-        Ok(CommandResult::with_details(
+        let mut result = CommandResult::new(
             "score".to_string(),
             raw_output,
             Operation::Score,
             true,
             message,
+        );
+
+        if let Some(score) = score {
+            result.score = Some(score.score);
+            result.max_score = score.out_of;
+            result.moves = score.moves;
+            if let Some(rank) = score.rank {
+                result.add_detail("rank".to_string(), serde_json::json!(rank));
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn input(&self) -> String {
+        "score".to_string()
+    }
+
+    fn operation(&self) -> Operation {
+        Operation::Score
+    }
+}
+
+/// Inventory command
+pub struct InventoryCommand;
+
+impl Command for InventoryCommand {
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        game.write(patterns.language.inventory_verb())?;
+        let raw_output = game.read_until(Some(&PROMPT_REGEX))?;
+
+        let mut details = HashMap::new();
+        let items = parse_inventory(&raw_output);
+        let message = items.as_ref().map(|items| match items.len() {
+            1 => "Carrying 1 item".to_string(),
+            n => format!("Carrying {} items", n),
+        });
+
+        if let Some(ref items) = items {
+            details.insert("items".to_string(), serde_json::json!(items));
+        }
+
+        Ok(CommandResult::with_details(
+            "inventory".to_string(),
+            raw_output,
+            Operation::Inventory,
+            items.is_some(),
+            message,
             details,
         ))
     }
 
     fn input(&self) -> String {
-        "score".to_string()
+        "inventory".to_string()
+    }
+
+    fn operation(&self) -> Operation {
+        Operation::Inventory
+    }
+}
+
+/// Look command
+pub struct LookCommand;
+
+impl Command for LookCommand {
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        game.write(patterns.language.look_verb())?;
+        let raw_output = game.read_until(Some(&PROMPT_REGEX))?;
+
+        let mut details = HashMap::new();
+        let room = parse_room(&raw_output);
+
+        if let Some(room) = room.as_ref() {
+            details.insert(
+                "description".to_string(),
+                serde_json::json!(room.description),
+            );
+            details.insert("objects".to_string(), serde_json::json!(room.objects));
+            details.insert("exits".to_string(), serde_json::json!(room.exits));
+        }
+
+        let mut result = CommandResult::with_details(
+            "look".to_string(),
+            raw_output,
+            Operation::Look,
+            room.is_some(),
+            None,
+            details,
+        );
+        result.location = room.map(|room| room.name);
+
+        Ok(result)
+    }
+
+    fn input(&self) -> String {
+        "look".to_string()
+    }
+
+    fn operation(&self) -> Operation {
+        Operation::Look
     }
 }
 
@@ -169,24 +366,25 @@ pub struct SaveCommand {
 }
 
 impl Command for SaveCommand {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult> {
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        let language = &patterns.language;
+        let done = &language.terminators.done;
+
         // Ensure saves directory exists
         std::fs::create_dir_all("saves").ok();
 
-        game.write("save")?;
-        game.read_until(Some(&FILENAME_PROMPT_REGEX))?;
+        game.write(language.save_verb())?;
+        game.read_until(Some(&language.filename_prompt))?;
         game.write(&self.savefile.filename())?;
 
-        let mut result = game.read_until(Some(
-            &Regex::new(r"(?i)Overwrite existing file\? |Ok\.|Failed\.|>").unwrap(),
-        ))?;
+        let mut result = game.read_until(Some(&language.terminators.save_file))?;
 
-        if result.contains("Overwrite existing file?") {
-            game.write("y")?;
-            result.push_str(&game.read_until(Some(&Regex::new(r"(?i)Ok\.|Failed\.|>").unwrap()))?);
+        if language.overwrite_prompt.is_match(&result) {
+            game.write(&language.yes)?;
+            result.push_str(&game.read_until(Some(done))?);
         }
 
-        let success = result.contains("Ok.");
+        let success = language.success.is_match(&result);
         let message = if success {
             Some(format!("[{}] Game saved successfully", self.savefile.slot))
         } else {
@@ -213,6 +411,10 @@ impl Command for SaveCommand {
     fn input(&self) -> String {
         "save".to_string()
     }
+
+    fn operation(&self) -> Operation {
+        Operation::Save
+    }
 }
 
 /// Restore command
@@ -221,14 +423,16 @@ pub struct RestoreCommand {
 }
 
 impl Command for RestoreCommand {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult> {
-        game.write("restore")?;
-        game.read_until(Some(&FILENAME_PROMPT_REGEX))?;
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        let language = &patterns.language;
+
+        game.write(language.restore_verb())?;
+        game.read_until(Some(&language.filename_prompt))?;
         game.write(&self.savefile.filename())?;
 
-        let result = game.read_until(Some(&Regex::new(r"(?i)Ok\.|Failed\.|>").unwrap()))?;
+        let result = game.read_until(Some(&language.terminators.done))?;
 
-        let success = result.contains("Ok.");
+        let success = language.success.is_match(&result);
         let message = if success {
             Some(format!(
                 "[{}] Game restored successfully",
@@ -258,18 +462,74 @@ impl Command for RestoreCommand {
     fn input(&self) -> String {
         "restore".to_string()
     }
+
+    fn operation(&self) -> Operation {
+        Operation::Restore
+    }
+}
+
+/// Restart command
+pub struct RestartCommand;
+
+impl Command for RestartCommand {
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        let language = &patterns.language;
+
+        // Mid-game the interpreter asks for confirmation; the end menu doesn't
+        game.write(language.restart_verb())?;
+        let mut raw_output = game.read_until(Some(&language.terminators.restart))?;
+
+        let confirmed = language.yes_no_question(&raw_output).is_some();
+        let restarted = if confirmed {
+            game.write(&language.yes)?;
+            let output = game.read_until(Some(&PROMPT_REGEX))?;
+            raw_output.push_str(&output);
+            output
+        } else {
+            raw_output.clone()
+        };
+
+        // The game restarted when it's back at a prompt without complaining,
+        // e.g. "Failed." or the parser not knowing the verb
+        let success = PROMPT_REGEX.is_match(&restarted)
+            && !language.failed.is_match(&restarted)
+            && (confirmed || patterns.failures.classify(&restarted).is_none());
+        let message = if success {
+            "Game restarted"
+        } else {
+            "Restart failed"
+        };
+
+        Ok(CommandResult::new(
+            "restart".to_string(),
+            raw_output,
+            Operation::Restart,
+            success,
+            Some(message.to_string()),
+        ))
+    }
+
+    fn input(&self) -> String {
+        "restart".to_string()
+    }
+
+    fn operation(&self) -> Operation {
+        Operation::Restart
+    }
 }
 
 /// Quit command
 pub struct QuitCommand;
 
 impl Command for QuitCommand {
-    fn execute(&self, game: &mut Dfrotz) -> Result<CommandResult> {
-        game.write("quit")?;
-        let raw_output = game.read_until(Some(&Regex::new(r"(?i)Are you sure|>").unwrap()))?;
+    fn execute(&self, game: &mut Dfrotz, patterns: &Patterns) -> Result<CommandResult> {
+        let language = &patterns.language;
 
-        if raw_output.to_lowercase().contains("are you sure") {
-            game.write("y")?;
+        game.write(language.quit_verb())?;
+        let raw_output = game.read_until(Some(&language.terminators.quit))?;
+
+        if language.quit_confirm.is_match(&raw_output) {
+            game.write(&language.yes)?;
             // Give it a moment to process
             std::thread::sleep(std::time::Duration::from_millis(100));
         }
@@ -288,4 +548,8 @@ impl Command for QuitCommand {
     fn input(&self) -> String {
         "quit".to_string()
     }
+
+    fn operation(&self) -> Operation {
+        Operation::Quit
+    }
 }
@@ -0,0 +1,152 @@
+//! Delta - What changed in the game between two turns
+
+use crate::{command_result::CommandResult, inventory::Item};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Changes since the previous turn
+///
+/// Fields are only set for state both turns reported, apart from the
+/// location, which is set whenever the player ends up somewhere new.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Delta {
+    /// Change in score
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    /// Moves taken since the previous turn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moves: Option<i32>,
+    /// Location the player moved to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Location the player moved from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_location: Option<String>,
+    /// Whether the location hadn't been seen before in this session
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub new_room: bool,
+    /// Items that appeared in the inventory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items_added: Vec<String>,
+    /// Items that left the inventory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items_removed: Vec<String>,
+}
+
+impl Delta {
+    /// Check if nothing changed
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Combine this delta with the one from the turn after it
+    pub fn then(self, later: Delta) -> Delta {
+        let sum = |a: Option<i32>, b: Option<i32>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+
+        let mut items_added = self.items_added;
+        let mut items_removed = self.items_removed;
+        for item in later.items_added {
+            match items_removed.iter().position(|i| *i == item) {
+                Some(index) => {
+                    items_removed.remove(index);
+                }
+                None => items_added.push(item),
+            }
+        }
+        for item in later.items_removed {
+            match items_added.iter().position(|i| *i == item) {
+                Some(index) => {
+                    items_added.remove(index);
+                }
+                None => items_removed.push(item),
+            }
+        }
+
+        Delta {
+            score: sum(self.score, later.score).filter(|score| *score != 0),
+            moves: sum(self.moves, later.moves),
+            location: later.location.or(self.location),
+            previous_location: self.previous_location.or(later.previous_location),
+            new_room: self.new_room || later.new_room,
+            items_added,
+            items_removed,
+        }
+    }
+}
+
+/// Game state remembered between turns to compute deltas
+#[derive(Debug, Clone, Default)]
+pub struct Tracker {
+    score: Option<i32>,
+    moves: Option<i32>,
+    location: Option<String>,
+    items: Option<Vec<String>>,
+    visited: HashSet<String>,
+}
+
+impl Tracker {
+    /// Compare a result's state with the previous turn's and remember it
+    ///
+    /// Uses the result's typed fields and its `items` detail, if any.
+    pub fn track(&mut self, result: &CommandResult) -> Delta {
+        let items = result
+            .get_detail("items")
+            .and_then(|value| serde_json::from_value::<Vec<Item>>(value.clone()).ok())
+            .map(|items| items.into_iter().map(|item| item.name).collect());
+
+        self.update(
+            result.score,
+            result.moves,
+            result.location.as_deref(),
+            items,
+        )
+    }
+
+    /// Compare a turn's state with the previous turn's and remember it
+    ///
+    /// `items` is `None` when the turn didn't list the inventory.
+    pub fn update(
+        &mut self,
+        score: Option<i32>,
+        moves: Option<i32>,
+        location: Option<&str>,
+        items: Option<Vec<String>>,
+    ) -> Delta {
+        let mut delta = Delta::default();
+
+        if let (Some(before), Some(after)) = (self.score, score) {
+            delta.score = Some(after - before).filter(|change| *change != 0);
+        }
+        if let (Some(before), Some(after)) = (self.moves, moves) {
+            delta.moves = Some(after - before).filter(|change| *change != 0);
+        }
+        if let Some(location) = location {
+            if self.location.as_deref() != Some(location) {
+                delta.location = Some(location.to_string());
+                delta.previous_location = self.location.replace(location.to_string());
+                delta.new_room = self.visited.insert(location.to_string());
+            }
+        }
+        if let Some(items) = items {
+            if let Some(ref before) = self.items {
+                delta.items_added = difference(&items, before);
+                delta.items_removed = difference(before, &items);
+            }
+            self.items = Some(items);
+        }
+
+        self.score = score.or(self.score);
+        self.moves = moves.or(self.moves);
+
+        delta
+    }
+}
+
+/// Items in `a` that aren't in `b`
+fn difference(a: &[String], b: &[String]) -> Vec<String> {
+    a.iter().filter(|item| !b.contains(item)).cloned().collect()
+}
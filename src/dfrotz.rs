@@ -1,12 +1,13 @@
 //! Dfrotz - Direct interface to dfrotz interpreter
 
-use crate::{Error, Result};
+use crate::{command_result::ReadEnd, Error, Result, PROMPT_REGEX};
 use regex::Regex;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::cell::Cell;
+use std::io::{BufWriter, Read, Write};
 use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 const TIMEOUT_SECS: u64 = 1;
 const CHUNK_SIZE: usize = 1024;
@@ -19,9 +20,12 @@ pub struct Dfrotz {
     dfrotz_path: String,
     timeout: Duration,
     command_delay: Duration,
+    seed: Option<u64>,
     child: Option<Child>,
     stdin: Option<BufWriter<ChildStdin>>,
-    stdout_reader: Option<Arc<Mutex<BufReader<ChildStdout>>>>,
+    /// Output chunks sent by the thread reading the interpreter's stdout
+    output: Option<Receiver<String>>,
+    last_read_end: Cell<Option<ReadEnd>>,
 }
 
 impl Dfrotz {
@@ -40,9 +44,11 @@ impl Dfrotz {
             dfrotz_path: dfrotz,
             timeout: Duration::from_secs(TIMEOUT_SECS),
             command_delay: Duration::from_millis(COMMAND_DELAY_MS),
+            seed: None,
             child: None,
             stdin: None,
-            stdout_reader: None,
+            output: None,
+            last_read_end: Cell::new(None),
         })
     }
 
@@ -58,13 +64,46 @@ impl Dfrotz {
             .unwrap_or(false)
     }
 
+    /// Seed the interpreter's random number generator, for repeatable games
+    ///
+    /// Takes effect the next time the process is started.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Seed passed to the interpreter, if any
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Version banner reported by the interpreter, e.g. "FROTZ V2.54"
+    pub fn version(&self) -> Option<String> {
+        let output = Command::new(&self.dfrotz_path)
+            .arg("-v")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(|line| line.to_string())
+    }
+
     /// Start the dfrotz process
     pub fn start(&mut self) -> Result<()> {
         if self.is_running() {
             return Ok(());
         }
 
-        let mut child = Command::new(&self.dfrotz_path)
+        let mut command = Command::new(&self.dfrotz_path);
+        if let Some(seed) = self.seed {
+            command.arg("-s").arg(seed.to_string());
+        }
+
+        let mut child = command
             .arg(&self.game_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -72,12 +111,16 @@ impl Dfrotz {
             .spawn()?;
 
         let stdin = BufWriter::new(child.stdin.take().unwrap());
-        let stdout = BufReader::new(child.stdout.take().unwrap());
-        let stdout_reader = Arc::new(Mutex::new(stdout));
+        let stdout = child.stdout.take().unwrap();
+
+        // Reads block until the interpreter prints something, so they happen
+        // on their own thread and `read_until` waits on the channel instead
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || read_output(stdout, sender));
 
         self.child = Some(child);
         self.stdin = Some(stdin);
-        self.stdout_reader = Some(stdout_reader);
+        self.output = Some(receiver);
 
         Ok(())
     }
@@ -107,60 +150,56 @@ impl Dfrotz {
     }
 
     /// Read until a pattern is matched or timeout occurs
+    ///
+    /// How the read ended is recorded for `take_read_end`: at the prompt, at
+    /// another pattern, or at the timeout.
     pub fn read_until(&self, pattern: Option<&Regex>) -> Result<String> {
         if !self.is_running() {
             return Ok(String::new());
         }
 
-        let stdout_reader = self.stdout_reader.as_ref().unwrap().clone();
-        let timeout = self.timeout;
-        let pattern_clone = pattern.cloned();
-
-        // Spawn a thread to read with timeout
-        let handle = thread::spawn(move || -> String {
-            let mut output = String::new();
-            let mut buffer = [0u8; CHUNK_SIZE];
-            let start = std::time::Instant::now();
-
-            loop {
-                if start.elapsed() >= timeout {
-                    break;
-                }
+        let Some(ref receiver) = self.output else {
+            return Ok(String::new());
+        };
+        let deadline = Instant::now() + self.timeout;
+        let mut output = String::new();
+        let mut end = ReadEnd::Timeout;
 
-                // Try to get lock with short timeout
-                if let Ok(mut reader) = stdout_reader.try_lock() {
-                    // Set a very short read timeout
-                    match reader.get_mut().read(&mut buffer) {
-                        Ok(0) => {
-                            // EOF reached
-                            thread::sleep(Duration::from_millis(10));
-                            continue;
-                        }
-                        Ok(n) => {
-                            if let Ok(chunk) = std::str::from_utf8(&buffer[..n]) {
-                                output.push_str(chunk);
-
-                                // Check if pattern matched
-                                if let Some(ref pattern) = pattern_clone {
-                                    if pattern.is_match(&output) {
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            // Would block or error, wait a bit
-                            thread::sleep(Duration::from_millis(10));
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok(chunk) => {
+                    output.push_str(&chunk);
+                    if let Some(pattern) = pattern {
+                        if pattern.is_match(&output) {
+                            end = if PROMPT_REGEX.is_match(&output) {
+                                ReadEnd::Prompt
+                            } else {
+                                ReadEnd::Pattern
+                            };
+                            break;
                         }
                     }
-                } else {
-                    thread::sleep(Duration::from_millis(10));
                 }
+                // Timed out, or the interpreter exited and all its output was read
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
             }
-            output
-        });
+        }
 
-        Ok(handle.join().unwrap_or_default())
+        // A timeout earlier in the same command isn't hidden by a later match
+        if self.last_read_end.get() != Some(ReadEnd::Timeout) {
+            self.last_read_end.set(Some(end));
+        }
+
+        Ok(output)
+    }
+
+    /// How the reads since the last call ended, clearing it for the next command
+    ///
+    /// This is how the last read ended, or `ReadEnd::Timeout` if any of the
+    /// reads timed out.
+    pub fn take_read_end(&self) -> Option<ReadEnd> {
+        self.last_read_end.take()
     }
 
     /// Check if the dfrotz process is running
@@ -181,7 +220,7 @@ impl Dfrotz {
 
         // Close stdin/stdout first
         self.stdin = None;
-        self.stdout_reader = None;
+        self.output = None;
 
         // Kill the process
         if let Some(ref mut child) = self.child {
@@ -194,6 +233,34 @@ impl Dfrotz {
     }
 }
 
+/// Send the interpreter's output in chunks until it closes its stdout
+///
+/// A multi-byte character split between two reads is held back until the
+/// rest of it arrives.
+fn read_output(mut stdout: ChildStdout, sender: mpsc::Sender<String>) {
+    let mut buffer = [0u8; CHUNK_SIZE];
+    let mut pending = Vec::new();
+
+    while let Ok(n) = stdout.read(&mut buffer) {
+        if n == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buffer[..n]);
+
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        let chunk = String::from_utf8_lossy(&pending[..valid]).to_string();
+        pending.drain(..valid);
+
+        if sender.send(chunk).is_err() {
+            break;
+        }
+    }
+}
+
 impl Drop for Dfrotz {
     fn drop(&mut self) {
         let _ = self.terminate();
@@ -1,16 +1,16 @@
 //! Formatters - Different output formatters for command results
 
-use crate::{command_result::CommandResult, PROMPT_REGEX};
-use regex::Regex;
+use crate::{
+    command_result::CommandResult, reflow::Reflow, theme::Theme, transcript::Transcript, Error,
+    Result,
+};
+use lazy_static::lazy_static;
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
-
-lazy_static::lazy_static! {
-    static ref SCORE_PATTERN: Regex = Regex::new(r"(?i)Score:\s*(\d+)").unwrap();
-    static ref MOVES_PATTERN: Regex = Regex::new(r"(?i)Moves:\s*(\d+)").unwrap();
-    static ref TIME_PATTERN: Regex = Regex::new(r"(\d{1,2}:\d{2}\s*(?:AM|PM))").unwrap();
-}
+use std::path::Path;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
 
 /// Formatter trait
 pub trait Formatter {
@@ -21,56 +21,77 @@ pub trait Formatter {
 }
 
 /// Shell formatter - interactive presentation with prompts and colors
-pub struct ShellFormatter;
+#[derive(Debug, Clone, Default)]
+pub struct ShellFormatter {
+    /// Reflow story text before showing it
+    pub reflow: Option<Reflow>,
+    /// Colors, plain when standard output isn't a terminal or NO_COLOR is set
+    pub theme: Theme,
+}
 
 impl Formatter for ShellFormatter {
     fn format(&self, result: &CommandResult) -> String {
-        if result.is_action_command() {
-            self.format_game_output(result)
-        } else {
-            self.format_system_feedback(result)
-        }
-    }
-
-    fn write_to(&self, result: &CommandResult, writer: &mut dyn Write) -> std::io::Result<()> {
-        if result.is_action_command() {
-            let display = result.message.as_ref().unwrap_or(&result.raw_output);
-            let (content, prompt) = self.extract_prompt(display);
-            write!(writer, "{}", content)?;
-            if let Some(p) = prompt {
-                let color = if result.success {
-                    "\x1b[32m"
+        match self.game_text(result) {
+            Some(text) => {
+                let prompt = if !result.segments.has_prompt() {
+                    String::new()
+                } else if result.success {
+                    Theme::paint(&self.theme.prompt, "> ")
                 } else {
-                    "\x1b[31m"
+                    Theme::paint(&self.theme.failure, "> ")
                 };
-                write!(writer, "{}{}\x1b[0m", color, p)?;
+                format!("{}\n\n{}", text, prompt)
             }
-        } else {
-            write!(writer, "{}", self.format(result))?;
+            None => self.format_system_feedback(result),
         }
-        Ok(())
     }
 }
 
 impl ShellFormatter {
-    fn format_system_feedback(&self, result: &CommandResult) -> String {
+    /// Story text for results that aren't shown as feedback
+    ///
+    /// A leading room name is painted in the theme's room color.
+    fn game_text(&self, result: &CommandResult) -> Option<String> {
         use crate::command_result::Operation;
 
-        if matches!(result.operation, Operation::Start | Operation::Score) {
-            return result.raw_output.clone();
+        let text = match result.operation {
+            Operation::Action => result.message.as_deref().unwrap_or(&result.segments.body),
+            Operation::Start | Operation::Score | Operation::Inventory | Operation::Look => {
+                &result.segments.body
+            }
+            _ => return None,
+        };
+        let text = reflow_text(self.reflow.as_ref(), text);
+
+        let heading = text.lines().next().unwrap_or("");
+        match result.location.as_deref() {
+            Some(location) if heading.trim() == location => Some(format!(
+                "{}{}",
+                Theme::paint(&self.theme.room, heading),
+                &text[heading.len()..]
+            )),
+            _ => Some(text),
         }
+    }
 
+    fn format_system_feedback(&self, result: &CommandResult) -> String {
         let prefix = if result.success {
-            "\x1b[32m✓\x1b[0m"
+            Theme::paint(&self.theme.success, "✓")
         } else {
-            "\x1b[31m✗\x1b[0m"
+            Theme::paint(&self.theme.failure, "✗")
         };
         let message = result.message.as_deref().unwrap_or("");
         let mut feedback = format!(
-            "{} {}: {}",
+            "{} {}",
             prefix,
-            result.operation.to_string().to_uppercase(),
-            message
+            Theme::paint(
+                &self.theme.feedback,
+                &format!(
+                    "{}: {}",
+                    result.operation.to_string().to_uppercase(),
+                    message
+                )
+            )
         );
 
         // Add details if present
@@ -85,39 +106,34 @@ impl ShellFormatter {
 
         feedback
     }
-
-    fn format_game_output(&self, result: &CommandResult) -> String {
-        result
-            .message
-            .as_ref()
-            .unwrap_or(&result.raw_output)
-            .clone()
-    }
-
-    fn extract_prompt(&self, content: &str) -> (String, Option<&'static str>) {
-        if PROMPT_REGEX.is_match(content) {
-            let cleaned = PROMPT_REGEX.replace_all(content, "").trim_end().to_string();
-            (format!("{}\n\n", cleaned), Some("> "))
-        } else {
-            (content.to_string(), None)
-        }
-    }
 }
 
 /// Text formatter - returns plain text output
-pub struct TextFormatter;
+#[derive(Debug, Clone, Default)]
+pub struct TextFormatter {
+    /// Reflow story text before showing it
+    pub reflow: Option<Reflow>,
+}
 
 impl Formatter for TextFormatter {
     fn format(&self, result: &CommandResult) -> String {
-        let content = result.message.as_ref().unwrap_or(&result.raw_output);
-        let cleaned = self.remove_prompt(content);
-        format!("{}\n\n", cleaned)
+        use crate::command_result::Operation;
+
+        // Parsed items and rooms are in the details; show what the game printed
+        let content = match result.operation {
+            Operation::Inventory | Operation::Look => &result.segments.body,
+            _ => result.message.as_deref().unwrap_or(&result.segments.body),
+        };
+        let content = reflow_text(self.reflow.as_ref(), content);
+        format!("{}\n\n", content.trim_end())
     }
 }
 
-impl TextFormatter {
-    fn remove_prompt(&self, content: &str) -> String {
-        PROMPT_REGEX.replace_all(content, "").trim_end().to_string()
+/// Apply `reflow` to text, if set
+fn reflow_text(reflow: Option<&Reflow>, text: &str) -> String {
+    match reflow {
+        Some(reflow) => reflow.apply(text),
+        None => text.to_string(),
     }
 }
 
@@ -134,167 +150,686 @@ impl Formatter for JsonFormatter {
 pub struct DataFormatter;
 
 impl DataFormatter {
-    fn extract_location(&self, text: &str) -> Option<String> {
-        let lines: Vec<&str> = text.lines().collect();
-        let first_line = lines.first()?.trim();
+    /// Parse state from a result
+    ///
+    /// Keys match the typed fields on `CommandResult`, which the session
+    /// fills from the status line, and the result's segments.
+    pub fn parse(&self, result: &CommandResult) -> HashMap<String, Value> {
+        let segments = &result.segments;
+        let location = result.location.clone();
+        let score = result.score;
+        let moves = result.moves;
+        let time = result.time.clone();
+        let has_prompt = segments.has_prompt();
+        let output = segments.body.clone();
 
-        if first_line.is_empty() {
-            return None;
+        let mut data = HashMap::new();
+        if let Some(loc) = location {
+            data.insert("location".to_string(), Value::String(loc));
+        }
+        if let Some(s) = score {
+            data.insert("score".to_string(), Value::Number(s.into()));
+        }
+        if let Some(m) = result.max_score {
+            data.insert("max_score".to_string(), Value::Number(m.into()));
+        }
+        if let Some(m) = moves {
+            data.insert("moves".to_string(), Value::Number(m.into()));
+        }
+        if let Some(t) = time {
+            data.insert("time".to_string(), Value::String(t));
+        }
+        if let Some(ref delta) = result.delta {
+            data.insert(
+                "delta".to_string(),
+                serde_json::to_value(delta).unwrap_or_default(),
+            );
         }
+        data.insert(
+            "game_state".to_string(),
+            Value::String(result.game_state.to_string()),
+        );
+        if let Some(ref status) = segments.status {
+            data.insert("status".to_string(), Value::String(status.clone()));
+        }
+        if let Some(ref prompt) = segments.prompt {
+            data.insert("prompt".to_string(), Value::String(prompt.clone()));
+        }
+        data.insert("output".to_string(), Value::String(output));
+        data.insert("has_prompt".to_string(), Value::Bool(has_prompt));
 
-        // Try different location extraction strategies
-        let location = self
-            .extract_location_with_stats(first_line)
-            .or_else(|| self.extract_standalone_location(first_line))?;
+        data
+    }
+}
 
-        if self.valid_location(&location) {
-            Some(location)
-        } else {
-            None
-        }
+impl Formatter for DataFormatter {
+    fn format(&self, result: &CommandResult) -> String {
+        let data = self.parse(result);
+        serde_json::to_string_pretty(&data).unwrap_or_else(|_| "{}".to_string())
     }
+}
+
+/// Markdown formatter - commands in bold, story text as paragraphs
+///
+/// System operations are rendered as blockquote notes. Story text is escaped
+/// so it can't turn into Markdown markup, and text laid out with leading
+/// spaces, such as nested inventories, is fenced to keep its layout.
+pub struct MarkdownFormatter;
+
+impl Formatter for MarkdownFormatter {
+    fn format(&self, result: &CommandResult) -> String {
+        use crate::command_result::Operation;
+
+        let mut markdown = String::new();
 
-    fn extract_location_with_stats(&self, line: &str) -> Option<String> {
-        // Handle formats like: " Canyon Bottom                    Score: 0        Moves: 26"
-        let parts: Vec<&str> = line.split("   ").collect();
-        if parts.len() >= 2 {
-            let candidate = parts[0].trim();
-            if !candidate.is_empty() {
-                return Some(candidate.to_string());
+        match result.operation {
+            Operation::Start => {}
+            Operation::Action | Operation::Inventory | Operation::Look => {
+                markdown.push_str(&format!("**> {}**\n\n", escape_markdown(&result.input)));
+            }
+            _ => {
+                let note = result.message.as_deref().unwrap_or(&result.segments.body);
+                let status = if result.success { "" } else { " (failed)" };
+                let mut lines = note.lines().filter(|line| !line.trim().is_empty());
+                markdown.push_str(&format!(
+                    "> **{}{}:** {}\n",
+                    capitalize(&result.operation.to_string()),
+                    status,
+                    escape_markdown(lines.next().unwrap_or("").trim())
+                ));
+                for line in lines {
+                    markdown.push_str(&format!("> {}\n", escape_markdown(line.trim())));
+                }
+                markdown.push('\n');
+                return markdown;
             }
         }
-        None
-    }
-
-    fn extract_standalone_location(&self, line: &str) -> Option<String> {
-        let candidate = line.trim();
-
-        // Only consider it a location if it doesn't contain stats and isn't an error message
-        if !candidate.contains("Score")
-            && !candidate.contains("Moves")
-            && !candidate.contains("AM")
-            && !candidate.contains("PM")
-            && !candidate.contains(":")
-            && !candidate.ends_with('.')
-            && !candidate.ends_with('!')
-            && !candidate.ends_with('?')
-            && candidate.len() < 50
-            && !candidate.to_lowercase().contains("response")
-        {
-            Some(candidate.to_string())
-        } else {
-            None
+
+        let body = &result.segments.body;
+        if !body.is_empty() {
+            markdown.push_str(&markdown_paragraphs(body));
+            markdown.push_str("\n\n");
         }
+
+        markdown
     }
+}
 
-    fn extract_score(&self, text: &str) -> Option<i32> {
-        SCORE_PATTERN
-            .captures(text)
-            .and_then(|cap| cap.get(1))
-            .and_then(|m| m.as_str().parse().ok())
+/// Render story text as paragraphs, or fence it if it relies on its layout
+fn markdown_paragraphs(text: &str) -> String {
+    if is_preformatted(text) {
+        // The fence must be longer than any run of backticks in the text
+        let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+        let fence = "`".repeat(longest.max(2) + 1);
+        format!("{}text\n{}\n{}", fence, text, fence)
+    } else {
+        text.lines()
+            .map(|line| escape_markdown(line.trim_end()))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
+}
+
+/// Check if text is laid out with leading spaces, e.g. a nested inventory
+fn is_preformatted(text: &str) -> bool {
+    text.lines().any(|line| line.starts_with("  "))
+}
 
-    fn extract_moves(&self, text: &str) -> Option<i32> {
-        MOVES_PATTERN
-            .captures(text)
-            .and_then(|cap| cap.get(1))
-            .and_then(|m| m.as_str().parse().ok())
+/// Escape characters that Markdown would treat as markup
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '&'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
     }
 
-    fn extract_time(&self, text: &str) -> Option<String> {
-        TIME_PATTERN
-            .captures(text)
-            .and_then(|cap| cap.get(1))
-            .map(|m| m.as_str().to_string())
+    // List markers only matter at the start of a line
+    let list_marker = escaped.starts_with("- ")
+        || escaped.starts_with("+ ")
+        || escaped.split_once(". ").is_some_and(|(number, _)| {
+            !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+        });
+    if list_marker {
+        match escaped.find(|c: char| !c.is_ascii_digit()) {
+            Some(0) | None => escaped.insert(0, '\\'),
+            Some(index) => escaped.insert(index, '\\'),
+        }
     }
 
-    fn valid_location(&self, location: &str) -> bool {
-        !location.is_empty()
-            && !location.starts_with("I don't ")
-            && !location.starts_with("I can't ")
-            && !location.starts_with("What do you ")
-            && !location.starts_with("You're ")
-            && !location.starts_with("You ")
-            && !location.starts_with("That's not ")
-            && !location.starts_with("I beg your pardon")
+    // A run of "=" or "-" would underline the line above as a heading
+    let underline = escaped.trim();
+    if !underline.is_empty()
+        && (underline.chars().all(|c| c == '=') || underline.chars().all(|c| c == '-'))
+    {
+        let indent = escaped.len() - escaped.trim_start().len();
+        escaped.insert(indent, '\\');
     }
 
-    pub fn parse(&self, result: &CommandResult) -> HashMap<String, Value> {
-        let text = &result.raw_output;
+    escaped
+}
 
-        let location = self.extract_location(text);
-        let score = self.extract_score(text);
-        let moves = self.extract_moves(text);
-        let time = self.extract_time(text);
-        let has_prompt = PROMPT_REGEX.is_match(text);
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
 
-        let mut cleaned = text.clone();
+/// Styles embedded in pages exported by `HtmlFormatter::page`
+const PAGE_CSS: &str = "
+body { margin: 2em auto; max-width: 44em; padding: 0 1em; font: 16px/1.5 Georgia, serif; color: #222; background: #fdfdf8; }
+header { border-bottom: 1px solid #ccc; margin-bottom: 2em; }
+header dl { display: grid; grid-template-columns: max-content auto; gap: 0.2em 1em; font-size: 0.9em; color: #555; }
+header dt { font-weight: bold; }
+header dd { margin: 0; }
+.turn { margin: 1.5em 0; }
+.command { margin: 0; font-weight: bold; }
+.command::before { content: '> '; color: #888; }
+kbd { font: inherit; }
+.output p { margin: 0.5em 0; }
+pre { font: 14px/1.4 Menlo, Consolas, monospace; white-space: pre-wrap; }
+.feedback { margin: 1em 0; padding: 0.4em 0.8em; border-left: 4px solid; font-size: 0.9em; }
+.feedback.success { border-color: #2a7; background: #eef9f1; }
+.feedback.failure { border-color: #c33; background: #fbeeee; }
+.action.failure .command { color: #a33; }
+";
+
+/// HTML formatter - semantic markup for each turn
+///
+/// Commands and story text become a `turn` section; system operations become
+/// `feedback` notes. Every element carries a `success` or `failure` class.
+/// `HtmlFormatter::page` wraps a whole transcript in a standalone page.
+pub struct HtmlFormatter;
+
+impl Formatter for HtmlFormatter {
+    fn format(&self, result: &CommandResult) -> String {
+        use crate::command_result::Operation;
+
+        let outcome = if result.success { "success" } else { "failure" };
+        let operation = result.operation.to_string();
+
+        match result.operation {
+            Operation::Start | Operation::Action | Operation::Inventory | Operation::Look => {
+                let mut html = format!("<section class=\"turn {} {}\">\n", operation, outcome);
+                if result.operation != Operation::Start {
+                    html.push_str(&format!(
+                        "<p class=\"command\"><kbd>{}</kbd></p>\n",
+                        escape_html(&result.input)
+                    ));
+                }
+                html.push_str(&format!(
+                    "<div class=\"output\">\n{}</div>\n</section>\n",
+                    html_paragraphs(&result.segments.body)
+                ));
+                html
+            }
+            _ => {
+                let note = result.message.as_deref().unwrap_or(&result.segments.body);
+                format!(
+                    "<aside class=\"feedback {} {}\"><strong>{}</strong> {}</aside>\n",
+                    operation,
+                    outcome,
+                    escape_html(&capitalize(&operation)),
+                    escape_html(note.trim())
+                )
+            }
+        }
+    }
+}
 
-        // Remove extracted patterns
-        if score.is_some() {
-            cleaned = SCORE_PATTERN.replace_all(&cleaned, "").to_string();
+impl HtmlFormatter {
+    /// Render a whole transcript as a standalone HTML page with embedded CSS
+    pub fn page(&self, transcript: &Transcript) -> String {
+        let header = &transcript.header;
+        let title = std::path::Path::new(&header.game)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(&header.game);
+
+        let mut metadata = Vec::new();
+        if let Some(ref story) = header.story {
+            metadata.push(("Release", format!("{} / {}", story.release, story.serial)));
         }
-        if moves.is_some() {
-            cleaned = MOVES_PATTERN.replace_all(&cleaned, "").to_string();
+        if let Some(ref interpreter) = header.interpreter {
+            metadata.push(("Interpreter", interpreter.clone()));
         }
-        if let Some(ref t) = time {
-            cleaned = cleaned.replace(t, "");
+        if let Some(seed) = header.seed {
+            metadata.push(("Seed", seed.to_string()));
         }
-        if has_prompt {
-            cleaned = PROMPT_REGEX.replace_all(&cleaned, "").to_string();
+        if let Some(language) = header.options.get("language") {
+            metadata.push(("Language", language.clone()));
         }
+        metadata.push(("Turns", transcript.results.len().to_string()));
+
+        let metadata: String = metadata
+            .iter()
+            .map(|(name, value)| format!("<dt>{}</dt><dd>{}</dd>\n", name, escape_html(value)))
+            .collect();
+        let turns: String = transcript
+            .results
+            .iter()
+            .map(|result| self.format(result))
+            .collect();
+
+        let language = header.options.get("language").map_or("en", String::as_str);
+        let title = escape_html(title);
+
+        let mut page = String::from("<!DOCTYPE html>\n");
+        page.push_str(&format!("<html lang=\"{}\">\n", escape_html(language)));
+        page.push_str("<head>\n<meta charset=\"utf-8\">\n");
+        page.push_str(&format!("<title>{}</title>\n", title));
+        page.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", PAGE_CSS));
+        page.push_str(&format!(
+            "<header>\n<h1>{}</h1>\n<dl>\n{}</dl>\n</header>\n",
+            title, metadata
+        ));
+        page.push_str(&format!("<main>\n{}</main>\n</body>\n</html>\n", turns));
+        page
+    }
+}
 
-        // Final cleanup
-        let output = self.final_cleanup(&cleaned);
+/// Render story text as paragraphs, keeping laid-out paragraphs preformatted
+fn html_paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| {
+            if is_preformatted(paragraph) {
+                format!("<pre>{}</pre>\n", escape_html(paragraph))
+            } else {
+                format!("<p>{}</p>\n", escape_html(paragraph.trim()))
+            }
+        })
+        .collect()
+}
 
-        let mut data = HashMap::new();
-        if let Some(loc) = location {
-            data.insert("location".to_string(), Value::String(loc));
+/// Escape text for use in HTML content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
         }
-        if let Some(s) = score {
-            data.insert("score".to_string(), Value::Number(s.into()));
+    }
+    escaped
+}
+
+/// Template formatter - renders results through a user-supplied template
+///
+/// Templates are plain text with `{{field}}` placeholders. Fields are looked
+/// up in the result's JSON form, so `{{input}}`, `{{operation}}` and
+/// `{{segments.body}}` all work, along with `{{details.<name>}}` for details
+/// and `{{data.<name>}}` for the fields parsed by `DataFormatter`. A field
+/// can fall back to others, as in `{{message | segments.body}}`. Fields a
+/// result can have but doesn't render as nothing, while names no result has
+/// are rejected when the template is parsed. `\{{` writes a literal `{{`.
+#[derive(Debug, Clone)]
+pub struct TemplateFormatter {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone)]
+enum TemplatePart {
+    Text(String),
+    /// Field paths, tried in order until one has a value
+    Field(Vec<Vec<String>>),
+}
+
+impl TemplateFormatter {
+    /// Create a formatter from template text
+    pub fn new(template: &str) -> Result<Self> {
+        Self::parse(template).map_err(|e| Error::InvalidTemplate("(inline)".to_string(), e))
+    }
+
+    /// Load a template from a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let template = std::fs::read_to_string(path)?;
+        Self::parse(&template).map_err(|e| Error::InvalidTemplate(path.display().to_string(), e))
+    }
+
+    fn parse(template: &str) -> std::result::Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                parts.push(TemplatePart::Text(format!("{}{{{{", &rest[..start - 1])));
+                rest = &rest[start + 2..];
+                continue;
+            }
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| {
+                format!(
+                    "unclosed placeholder at byte {}",
+                    template.len() - rest.len() + start
+                )
+            })?;
+
+            let mut paths = Vec::new();
+            for name in after[..end].split('|') {
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(format!("empty field name in {{{{{}}}}}", &after[..end]));
+                }
+                let root = name.split('.').next().unwrap_or(name);
+                if !Self::fields().iter().any(|field| field == root) {
+                    return Err(format!(
+                        "unknown field '{}' in {{{{{}}}}}",
+                        root,
+                        &after[..end]
+                    ));
+                }
+                paths.push(name.split('.').map(str::to_string).collect());
+            }
+            parts.push(TemplatePart::Field(paths));
+            rest = &after[end + 2..];
         }
-        if let Some(m) = moves {
-            data.insert("moves".to_string(), Value::Number(m.into()));
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
         }
-        if let Some(t) = time {
-            data.insert("time".to_string(), Value::String(t));
+
+        Ok(Self { parts })
+    }
+
+    /// Top-level fields a template can refer to
+    ///
+    /// The result's fields from its schema, plus `details` and `data`.
+    pub fn fields() -> Vec<String> {
+        let schema = CommandResult::schema();
+        let mut fields: Vec<String> = schema["properties"]
+            .as_object()
+            .map(|properties| properties.keys().cloned().collect())
+            .unwrap_or_default();
+        fields.extend(["details".to_string(), "data".to_string()]);
+        fields
+    }
+
+    /// Values a template can refer to for a result
+    ///
+    /// This is the result's JSON form, with its details also grouped under
+    /// `details` and the fields parsed by `DataFormatter` under `data`.
+    pub fn context(result: &CommandResult) -> Value {
+        let mut context = serde_json::to_value(result).unwrap_or(Value::Null);
+        if let Value::Object(ref mut fields) = context {
+            fields.insert(
+                "details".to_string(),
+                Value::Object(result.details.clone().into_iter().collect()),
+            );
+            fields.insert(
+                "data".to_string(),
+                Value::Object(DataFormatter.parse(result).into_iter().collect()),
+            );
         }
-        data.insert("prompt".to_string(), Value::String(">".to_string()));
-        data.insert("output".to_string(), Value::String(output));
-        data.insert("has_prompt".to_string(), Value::Bool(has_prompt));
+        context
+    }
+}
 
-        data
+impl Formatter for TemplateFormatter {
+    fn format(&self, result: &CommandResult) -> String {
+        let context = Self::context(result);
+        let mut output = String::new();
+
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Field(paths) => {
+                    let value = paths
+                        .iter()
+                        .filter_map(|path| lookup(&context, path))
+                        .map(render_value)
+                        .find(|value| !value.is_empty());
+                    output.push_str(&value.unwrap_or_default());
+                }
+            }
+        }
+
+        output
     }
+}
+
+/// Follow a field path through objects and arrays
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| match value {
+        Value::Object(fields) => fields.get(key),
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
 
-    fn final_cleanup(&self, text: &str) -> String {
-        // Clean up excessive whitespace but preserve paragraph structure
-        let cleaned = text.trim();
-        let cleaned = Regex::new(r"\n{3,}").unwrap().replace_all(cleaned, "\n\n");
-        let cleaned = Regex::new(r"(?m)^\s+$").unwrap().replace_all(&cleaned, "");
-        let cleaned = Regex::new(r"[ \t]+$").unwrap().replace_all(&cleaned, "");
-        cleaned.trim().to_string()
+/// Text for a value: strings as they are, lists joined with commas
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(render_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => value.to_string(),
     }
 }
 
-impl Formatter for DataFormatter {
+/// Kind of event a result represents in a stream of results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Event {
+    /// The game started
+    Start,
+    /// A command was sent to the game, including look, inventory and score
+    Turn,
+    /// A system operation such as save, restore or a hint
+    System,
+    /// The game was quit
+    End,
+}
+
+impl Event {
+    /// Event for a result, from its operation
+    pub fn for_result(result: &CommandResult) -> Self {
+        use crate::command_result::Operation;
+
+        match result.operation {
+            Operation::Start => Event::Start,
+            Operation::Action | Operation::Look | Operation::Inventory | Operation::Score => {
+                Event::Turn
+            }
+            Operation::Quit => Event::End,
+            _ => Event::System,
+        }
+    }
+}
+
+/// NDJSON formatter - one enveloped JSON object per line
+///
+/// Each result is wrapped with the session id, turn number, game name and
+/// event type, so a stream of results can be parsed line by line.
+pub struct NdjsonFormatter {
+    pub session_id: String,
+    pub game: String,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    session_id: &'a str,
+    turn: u64,
+    game: &'a str,
+    event: Event,
+    result: &'a CommandResult,
+}
+
+impl Formatter for NdjsonFormatter {
     fn format(&self, result: &CommandResult) -> String {
-        let data = self.parse(result);
-        serde_json::to_string_pretty(&data).unwrap_or_else(|_| "{}".to_string())
+        let envelope = Envelope {
+            session_id: &self.session_id,
+            turn: result.turn,
+            game: &self.game,
+            event: Event::for_result(result),
+            result,
+        };
+        let json = serde_json::to_string(&envelope).unwrap_or_else(|_| "{}".to_string());
+        format!("{}\n", json)
+    }
+}
+
+/// Context some formatters need about the session they format
+#[derive(Debug, Clone, Default)]
+pub struct FormatterOptions {
+    /// Session id, from `Session::id`
+    pub session_id: String,
+    /// Name of the game being played
+    pub game: String,
+    /// Reflow story text in the text and shell formatters
+    pub reflow: Option<Reflow>,
+    /// Shell colors, or `None` for `Theme::for_stdout`
+    pub theme: Option<Theme>,
+    /// Template for the template formatter
+    pub template: Option<TemplateFormatter>,
+}
+
+/// Creates a formatter from the session context
+pub type FormatterFactory =
+    Box<dyn Fn(&FormatterOptions) -> Result<Box<dyn Formatter>> + Send + Sync>;
+
+lazy_static! {
+    static ref GLOBAL_REGISTRY: RwLock<FormatterRegistry> = RwLock::new(FormatterRegistry::new());
+}
+
+/// Formatters by name
+///
+/// Starts with the built-in formatters. Library users can register their own,
+/// either in a registry they keep or in the global one that `Formatters` and
+/// the command line use. Names are case insensitive.
+pub struct FormatterRegistry {
+    factories: BTreeMap<String, FormatterFactory>,
+}
+
+impl FormatterRegistry {
+    /// Create a registry holding the built-in formatters
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register("shell", |options| {
+            Ok(Box::new(ShellFormatter {
+                reflow: options.reflow,
+                theme: options.theme.clone().unwrap_or_default(),
+            }))
+        });
+        registry.register("text", |options| {
+            Ok(Box::new(TextFormatter {
+                reflow: options.reflow,
+            }))
+        });
+        registry.register("json", |_| Ok(Box::new(JsonFormatter)));
+        registry.register("data", |_| Ok(Box::new(DataFormatter)));
+        registry.register("markdown", |_| Ok(Box::new(MarkdownFormatter)));
+        registry.register("html", |_| Ok(Box::new(HtmlFormatter)));
+        registry.register("ndjson", |options| {
+            Ok(Box::new(NdjsonFormatter {
+                session_id: options.session_id.clone(),
+                game: options.game.clone(),
+            }))
+        });
+        registry.register("template", |options| match options.template {
+            Some(ref template) => Ok(Box::new(template.clone())),
+            None => Err(Error::MissingTemplate),
+        });
+
+        registry
+    }
+
+    /// Create a registry without any formatters
+    pub fn empty() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Register a formatter under `name`, replacing any with the same name
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&FormatterOptions) -> Result<Box<dyn Formatter>> + Send + Sync + 'static,
+    {
+        self.factories
+            .insert(name.to_lowercase(), Box::new(factory));
+    }
+
+    /// Check if a formatter is registered under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(&name.to_lowercase())
+    }
+
+    /// Registered names, in alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        self.factories.keys().cloned().collect()
+    }
+
+    /// Create the formatter registered under `name`
+    pub fn create(&self, name: &str, options: &FormatterOptions) -> Result<Box<dyn Formatter>> {
+        match self.factories.get(&name.to_lowercase()) {
+            Some(factory) => factory(options),
+            None => Err(Error::UnknownFormatter(
+                name.to_string(),
+                self.names().join(", "),
+            )),
+        }
+    }
+}
+
+impl Default for FormatterRegistry {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// Formatters module - factory for creating formatters
+/// Formatters module - creates formatters from the global registry
 pub struct Formatters;
 
 impl Formatters {
-    pub fn by_name(name: &str) -> Box<dyn Formatter> {
-        match name.to_lowercase().as_str() {
-            "shell" => Box::new(ShellFormatter),
-            "text" => Box::new(TextFormatter),
-            "json" => Box::new(JsonFormatter),
-            "data" => Box::new(DataFormatter),
-            _ => Box::new(ShellFormatter),
-        }
+    /// Create a formatter by name with default options
+    pub fn by_name(name: &str) -> Result<Box<dyn Formatter>> {
+        Self::by_name_with(name, &FormatterOptions::default())
+    }
+
+    /// Create a formatter by name, giving it the session context in `options`
+    ///
+    /// Unknown names are an error listing the registered ones.
+    pub fn by_name_with(name: &str, options: &FormatterOptions) -> Result<Box<dyn Formatter>> {
+        Self::registry().create(name, options)
+    }
+
+    /// Register a formatter globally, so `by_name` and the command line can select it
+    pub fn register<F>(name: &str, factory: F)
+    where
+        F: Fn(&FormatterOptions) -> Result<Box<dyn Formatter>> + Send + Sync + 'static,
+    {
+        GLOBAL_REGISTRY
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .register(name, factory);
+    }
+
+    /// Names of the globally registered formatters
+    pub fn names() -> Vec<String> {
+        Self::registry().names()
+    }
+
+    fn registry() -> RwLockReadGuard<'static, FormatterRegistry> {
+        GLOBAL_REGISTRY
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
     }
 }
@@ -1,8 +1,55 @@
 //! Gamefile - Represents a game file and its metadata
 
-use crate::{game_dir, Error, Result};
+use crate::{game_dir, hints::HINTS_EXTENSION, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
 use std::path::PathBuf;
 
+/// Size of the Z-Machine story file header
+const HEADER_SIZE: usize = 64;
+
+/// Identifying information from a Z-Machine story file header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoryMetadata {
+    /// Z-Machine version (1-8)
+    pub version: u8,
+    /// Release number
+    pub release: u16,
+    /// Serial number, usually the compile date as YYMMDD
+    pub serial: String,
+    /// Header checksum of the story file
+    pub checksum: u16,
+    /// Inform compiler version, e.g. "6.21", when the compiler stamped one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<String>,
+}
+
+impl StoryMetadata {
+    /// Parse metadata from the first bytes of a story file
+    pub fn from_header(header: &[u8]) -> Option<Self> {
+        if header.len() < HEADER_SIZE || !(1..=8).contains(&header[0]) {
+            return None;
+        }
+
+        let word = |offset: usize| u16::from_be_bytes([header[offset], header[offset + 1]]);
+        let serial = String::from_utf8_lossy(&header[0x12..0x18]).to_string();
+        let stamp = &header[0x3C..0x40];
+        let compiler = if stamp[0].is_ascii_digit() && stamp[1] == b'.' {
+            Some(String::from_utf8_lossy(stamp).to_string())
+        } else {
+            None
+        };
+
+        Some(Self {
+            version: header[0],
+            release: word(0x02),
+            serial,
+            checksum: word(0x1C),
+            compiler,
+        })
+    }
+}
+
 /// Represents a Z-Machine game file
 #[derive(Debug, Clone)]
 pub struct Gamefile {
@@ -20,6 +67,7 @@ impl Gamefile {
     ///
     /// If the input contains a path separator, it's treated as a full path.
     /// Otherwise, it's treated as a game name and searched in the games directory.
+    /// Hint files living next to the stories are never matched.
     pub fn from_input(input: &str) -> Result<Self> {
         if input.contains('/') || input.contains('\\') {
             let path = PathBuf::from(input);
@@ -39,7 +87,7 @@ impl Gamefile {
                     let path = entry.path();
                     if path.is_file() {
                         if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                            if file_name.starts_with(input) {
+                            if file_name.starts_with(input) && !is_hint_file(file_name) {
                                 matches.push(path);
                             }
                         }
@@ -76,6 +124,19 @@ impl Gamefile {
         self.path.exists()
     }
 
+    /// Read the story file header
+    pub fn metadata(&self) -> Result<StoryMetadata> {
+        let mut header = [0u8; HEADER_SIZE];
+        std::fs::File::open(&self.path)?.read_exact(&mut header)?;
+        StoryMetadata::from_header(&header)
+            .ok_or_else(|| Error::InvalidStoryFile(self.name.clone()))
+    }
+
+    /// Path of the hint file for this game, next to the story file
+    pub fn hints_path(&self) -> PathBuf {
+        self.path.with_extension(HINTS_EXTENSION)
+    }
+
     /// Get the full path to the game file
     pub fn full_path(&self) -> Result<String> {
         self.path
@@ -84,3 +145,7 @@ impl Gamefile {
             .or_else(|_| Ok(self.path.to_string_lossy().to_string()))
     }
 }
+
+fn is_hint_file(file_name: &str) -> bool {
+    file_name.ends_with(&format!(".{}", HINTS_EXTENSION))
+}
@@ -0,0 +1,68 @@
+//! Hints - Progressive hints loaded from a file next to the story
+//!
+//! A hint file is JSON named after the story, e.g. `games/zork1.hints.json`
+//! for `games/zork1.z5`. Like InvisiClues, each topic holds a list of hints
+//! that reveal a little more each time they're asked for.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Extension replacing the story file's own to find its hints
+pub const HINTS_EXTENSION: &str = "hints.json";
+
+/// A question a player can get hints for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintTopic {
+    /// The question, e.g. "How do I get into the house?"
+    pub topic: String,
+    /// Room the topic belongs to; topics without one apply everywhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    /// Hints from the gentlest nudge to the full answer
+    pub hints: Vec<String>,
+}
+
+/// All hint topics for a game
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hints {
+    pub topics: Vec<HintTopic>,
+}
+
+impl Hints {
+    /// Load hints from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json)
+            .map_err(|e| Error::InvalidHintFile(path.display().to_string(), e.to_string()))
+    }
+
+    /// Topics for a room, followed by topics that apply everywhere
+    pub fn for_room(&self, room: Option<&str>) -> Vec<&HintTopic> {
+        let in_room = self.topics.iter().filter(|topic| in_room(topic, room));
+        let general = self.topics.iter().filter(|topic| topic.room.is_none());
+
+        in_room.chain(general).collect()
+    }
+
+    /// Find the topic a query refers to
+    ///
+    /// Topics are matched case-insensitively on any part of their text, and
+    /// topics for the current room win over the rest.
+    pub fn find(&self, query: &str, room: Option<&str>) -> Option<&HintTopic> {
+        let query = query.trim().to_lowercase();
+        let matches = |topic: &&HintTopic| topic.topic.to_lowercase().contains(&query);
+
+        self.for_room(room)
+            .into_iter()
+            .find(matches)
+            .or_else(|| self.topics.iter().find(matches))
+    }
+}
+
+fn in_room(topic: &HintTopic, room: Option<&str>) -> bool {
+    match (topic.room.as_deref(), room) {
+        (Some(topic_room), Some(room)) => topic_room.eq_ignore_ascii_case(room),
+        _ => false,
+    }
+}
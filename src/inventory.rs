@@ -0,0 +1,162 @@
+//! Inventory - Parses the items listed by the game's inventory command
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// Line introducing the inventory; group 1 is any text on the same line
+    static ref HEADER_REGEX: Regex = Regex::new(
+        r"(?im)^\s*(?:You are carrying|You're carrying|You are holding|You have|I'm carrying|I am carrying)(?: the following)?(.*)$"
+    )
+    .unwrap();
+
+    /// Output for an empty inventory
+    static ref EMPTY_REGEX: Regex = Regex::new(
+        r"(?i)You are empty[- ]handed|You(?: are|'re) (?:not )?carrying (?:nothing|anything)|You have nothing|I'm carrying nothing|I'm not carrying anything"
+    )
+    .unwrap();
+
+    /// Parenthesized notes after an item, e.g. "(being worn)"
+    static ref NOTE_REGEX: Regex = Regex::new(r"\(([^()]*)\)").unwrap();
+}
+
+/// An item in the player's inventory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Item {
+    /// Item name without a leading article
+    pub name: String,
+    /// Whether the item is being worn
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub worn: bool,
+    /// Other notes the game printed, e.g. "providing light"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Items inside or on this item
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<Item>,
+}
+
+impl Item {
+    /// Parse an item from a line like "a cloak (being worn)"
+    pub fn parse(text: &str) -> Option<Self> {
+        let notes: Vec<String> = NOTE_REGEX
+            .captures_iter(text)
+            .map(|captures| captures[1].trim().to_string())
+            .collect();
+        let name = NOTE_REGEX.replace_all(text, "");
+        let name = name.trim().trim_end_matches(['.', ',']).trim();
+        let name = ["a ", "an ", "the ", "some "]
+            .iter()
+            .find_map(|article| {
+                name.get(..article.len())
+                    .filter(|prefix| prefix.eq_ignore_ascii_case(article))
+                    .map(|_| &name[article.len()..])
+            })
+            .unwrap_or(name)
+            .trim();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        // "(providing light and being worn)" keeps "providing light"
+        let worn = notes.iter().any(|note| note.contains("worn"));
+        let notes = notes
+            .iter()
+            .flat_map(|note| {
+                if note.contains("worn") {
+                    note.split(" and ")
+                        .filter(|part| !part.contains("worn"))
+                        .map(str::to_string)
+                        .collect()
+                } else {
+                    vec![note.clone()]
+                }
+            })
+            .collect();
+
+        Some(Self {
+            name: name.to_string(),
+            worn,
+            notes,
+            contents: Vec::new(),
+        })
+    }
+}
+
+/// Parse the items from inventory output
+///
+/// Handles indented lists, where deeper indentation means the item is inside
+/// the one above, and single-line lists like "You have a lamp and a sword."
+/// Returns `None` when the output doesn't look like an inventory.
+pub fn parse_inventory(output: &str) -> Option<Vec<Item>> {
+    if EMPTY_REGEX.is_match(output) {
+        return Some(Vec::new());
+    }
+
+    let captures = HEADER_REGEX.captures(output)?;
+    let inline = captures[1].trim().trim_start_matches(':').trim();
+
+    if !inline.is_empty() {
+        return Some(parse_inline(inline));
+    }
+
+    let after_header = &output[captures.get(0)?.end()..];
+    let mut entries = Vec::new();
+
+    for line in after_header.lines() {
+        let text = line.trim();
+        if text.is_empty() {
+            if entries.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if text.starts_with('>') {
+            break;
+        }
+        // "The bottle contains:" introduces the items below it
+        if text.ends_with(':') {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        if let Some(item) = Item::parse(text) {
+            entries.push((indent, item));
+        }
+    }
+
+    Some(nest(&entries))
+}
+
+/// Split "a lamp, a sword and a bag." into items
+fn parse_inline(text: &str) -> Vec<Item> {
+    text.trim_end_matches('.')
+        .replace(" and ", ", ")
+        .split(", ")
+        .filter_map(Item::parse)
+        .collect()
+}
+
+/// Build a tree from items and their indentation
+fn nest(entries: &[(usize, Item)]) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut index = 0;
+
+    while index < entries.len() {
+        let (indent, ref item) = entries[index];
+        let children = entries[index + 1..]
+            .iter()
+            .take_while(|(child_indent, _)| *child_indent > indent)
+            .count();
+
+        let mut item = item.clone();
+        item.contents = nest(&entries[index + 1..index + 1 + children]);
+        items.push(item);
+
+        index += 1 + children;
+    }
+
+    items
+}
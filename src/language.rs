@@ -0,0 +1,492 @@
+//! Language - Per-language text the crate matches and sends
+//!
+//! Non-English games use their own verbs for system commands and phrase
+//! prompts, confirmations and errors in their own language. A language
+//! profile collects everything that has to change together.
+
+use crate::{
+    command_result::{FailureKind, GameState},
+    patterns::{EndingPatterns, FailurePatterns},
+    score::{ScoreFormat, SCORE_FORMATS},
+    Error, Result, FILENAME_PROMPT_REGEX, PROMPT_REGEX, YES_NO_REGEX,
+};
+use regex::Regex;
+
+/// Names of the built-in language profiles
+pub const LANGUAGE_NAMES: &[&str] = &["en", "de", "es"];
+
+/// Verbs, prompts and patterns for one language
+#[derive(Debug, Clone)]
+pub struct LanguageProfile {
+    /// Short language code, e.g. "de"
+    pub name: String,
+    /// Verbs intercepted as save; the first is sent to the game
+    pub save_verbs: Vec<String>,
+    /// Verbs intercepted as restore; the first is sent to the game
+    pub restore_verbs: Vec<String>,
+    /// Verbs intercepted as score; the first is sent to the game
+    pub score_verbs: Vec<String>,
+    /// Verbs intercepted as inventory; the first is sent to the game
+    pub inventory_verbs: Vec<String>,
+    /// Verbs intercepted as look; the first is sent to the game
+    pub look_verbs: Vec<String>,
+    /// Verbs intercepted as restart; the first is sent to the game
+    pub restart_verbs: Vec<String>,
+    /// Verbs asking for a hint, when the game has a hint file
+    pub hint_verbs: Vec<String>,
+    /// Verbs intercepted as quit; the first is sent to the game
+    pub quit_verbs: Vec<String>,
+    /// Answer sent for "yes"
+    pub yes: String,
+    /// Answer sent for "no"
+    pub no: String,
+    /// Word joining alternatives in a question, e.g. "or"
+    pub or_word: String,
+    /// Prompt asking for a save file name
+    pub filename_prompt: Regex,
+    /// Prompt asking to overwrite an existing save file
+    pub overwrite_prompt: Regex,
+    /// Confirmation asked before quitting
+    pub quit_confirm: Regex,
+    /// Message printed after a successful save or restore
+    pub success: Regex,
+    /// Message printed after a failed save or restore
+    pub failed: Regex,
+    /// Yes/no question at the end of the output
+    ///
+    /// Group 1, when present, captures the question text.
+    pub yes_no: Regex,
+    /// Formats used to parse score output
+    pub score_formats: Vec<ScoreFormat>,
+    /// Failure patterns for this language
+    ///
+    /// `None` keeps the pack chosen for the story's parser.
+    pub failures: Option<FailurePatterns>,
+    /// End banners and menu for this language
+    ///
+    /// `None` keeps the patterns chosen for the story's parser.
+    pub endings: Option<EndingPatterns>,
+    /// Patterns ending reads, combined from the prompts and messages above
+    ///
+    /// Call `update_terminators` after changing any of them.
+    pub terminators: Terminators,
+}
+
+/// Patterns ending the reads of system commands
+///
+/// Each combines several of a profile's patterns. They are built once per
+/// profile rather than on every command.
+#[derive(Debug, Clone)]
+pub struct Terminators {
+    /// Save or restore finished: success, failure or the prompt
+    pub done: Regex,
+    /// Save file name sent: the overwrite prompt, or finished
+    pub save_file: Regex,
+    /// Restart sent: the prompt or a confirmation question
+    pub restart: Regex,
+    /// Quit sent: the confirmation or the prompt
+    pub quit: Regex,
+}
+
+impl Terminators {
+    /// Combine the patterns of a profile
+    pub fn new(language: &LanguageProfile) -> Self {
+        Self {
+            done: any_of(&[&language.success, &language.failed, &PROMPT_REGEX]),
+            save_file: any_of(&[
+                &language.overwrite_prompt,
+                &language.success,
+                &language.failed,
+                &PROMPT_REGEX,
+            ]),
+            restart: any_of(&[&PROMPT_REGEX, &language.yes_no]),
+            quit: any_of(&[&language.quit_confirm, &PROMPT_REGEX]),
+        }
+    }
+
+    /// Terminators that end every read at the prompt
+    pub fn prompt_only() -> Self {
+        Self {
+            done: PROMPT_REGEX.clone(),
+            save_file: PROMPT_REGEX.clone(),
+            restart: PROMPT_REGEX.clone(),
+            quit: PROMPT_REGEX.clone(),
+        }
+    }
+}
+
+impl LanguageProfile {
+    /// Load a built-in profile by language code
+    pub fn by_name(name: &str) -> Result<Self> {
+        match name {
+            "en" => Ok(Self::english()),
+            "de" => Ok(Self::german()),
+            "es" => Ok(Self::spanish()),
+            _ => Err(Error::UnknownLanguage(
+                name.to_string(),
+                LANGUAGE_NAMES.join(", "),
+            )),
+        }
+    }
+
+    /// English, as used by Infocom and the Inform libraries
+    pub fn english() -> Self {
+        Self {
+            name: "en".to_string(),
+            save_verbs: verbs(&["save"]),
+            restore_verbs: verbs(&["restore"]),
+            score_verbs: verbs(&["score"]),
+            inventory_verbs: verbs(&["inventory", "i", "inv"]),
+            look_verbs: verbs(&["look", "l"]),
+            restart_verbs: verbs(&["restart"]),
+            hint_verbs: verbs(&["hint", "hints"]),
+            quit_verbs: verbs(&["quit"]),
+            yes: "y".to_string(),
+            no: "n".to_string(),
+            or_word: "or".to_string(),
+            filename_prompt: FILENAME_PROMPT_REGEX.clone(),
+            overwrite_prompt: regex(r"(?i)Overwrite existing file\? "),
+            quit_confirm: regex(r"(?i)Are you sure"),
+            success: regex(r"Ok\."),
+            failed: regex(r"(?i)Failed\."),
+            yes_no: YES_NO_REGEX.clone(),
+            score_formats: SCORE_FORMATS.clone(),
+            failures: None,
+            endings: None,
+            terminators: Terminators::prompt_only(),
+        }
+        .with_terminators()
+    }
+
+    /// German, as used by the deform Inform library
+    pub fn german() -> Self {
+        Self {
+            name: "de".to_string(),
+            save_verbs: verbs(&["speichern", "save"]),
+            restore_verbs: verbs(&["laden", "restore"]),
+            score_verbs: verbs(&["punkte", "score"]),
+            inventory_verbs: verbs(&["inventar", "i", "inventory"]),
+            look_verbs: verbs(&["schau", "umsehen", "l", "look"]),
+            restart_verbs: verbs(&["neustart", "neustarten", "restart"]),
+            hint_verbs: verbs(&["hinweis", "tipp", "hint"]),
+            quit_verbs: verbs(&["ende", "beenden", "quit"]),
+            yes: "j".to_string(),
+            no: "n".to_string(),
+            or_word: "oder".to_string(),
+            filename_prompt: regex(
+                r"Please enter a filename \[.*\]: |(?i)Bitte (?:einen )?Dateinamen eingeben.*: ",
+            ),
+            overwrite_prompt: regex(r"(?i)Overwrite existing file\? |Datei überschreiben\? "),
+            quit_confirm: regex(r"(?i)wirklich (?:aufhören|beenden)|Are you sure"),
+            success: regex(r"Ok\."),
+            failed: regex(r"(?i)fehlgeschlagen|Failed\."),
+            yes_no: regex(
+                r"(?i)(?:(?:^|[.!?]\s+|\n)\s*((?:willst|möchtest|wollen|möchten|bist) (?:du|Sie)\b[^?.!>]*\?)|(\((?:j/n|ja/nein)\)))[ \t]*(?:>[ \t]*)?\s*\z",
+            ),
+            score_formats: vec![
+                score_format(
+                    "deform",
+                    r"(?i)(?P<score>-?\d+) (?:Punkte? )?(?:von|aus) (?:insgesamt |maximal )?(?P<out_of>\d+)",
+                ),
+                score_format("moves", r"(?i)\bin (?P<moves>\d+) (?:Zügen|Zug)\b"),
+                score_format(
+                    "rank",
+                    r"(?i)(?:den Rang|den Titel) (?:eines |einer |eine |ein )?(?P<rank>[^.!\n]+)",
+                ),
+            ],
+            failures: Some(FailurePatterns::new(
+                "de",
+                vec![
+                    (
+                        FailureKind::Disambiguation,
+                        regex(r"(?i)(?:Welche[nrs]?|Wen|Was) (?:\w+ )?meinst du"),
+                    ),
+                    (
+                        FailureKind::UnknownVerb,
+                        regex(r"(?i)(?:Dieses|Das) Verb kenne ich nicht"),
+                    ),
+                    (
+                        FailureKind::UnknownVerb,
+                        regex(r"(?i)Ich verstehe (?:den|diesen) Satz nicht"),
+                    ),
+                    (
+                        FailureKind::UnknownVerb,
+                        regex(r"(?i)Ich habe nur verstanden"),
+                    ),
+                    (FailureKind::UnknownNoun, regex(r"(?i)Ich kenne das Wort")),
+                    (
+                        FailureKind::NotVisible,
+                        regex(r"(?i)(?:So etwas|Das) kannst du (?:hier )?nicht sehen"),
+                    ),
+                    (
+                        FailureKind::NotVisible,
+                        regex(r"(?i)Du siehst (?:hier )?(?:so etwas|nichts)"),
+                    ),
+                    (
+                        FailureKind::Incomplete,
+                        regex(r"(?i)(?:Was|Wen|Wohin) (?:willst|möchtest) du"),
+                    ),
+                    (FailureKind::Incomplete, regex(r"(?i)Wie bitte\?")),
+                    (FailureKind::Impossible, regex(r"(?i)Das kannst du nicht")),
+                    (
+                        FailureKind::Impossible,
+                        regex(r"(?i)Du kannst (?:hier )?nicht"),
+                    ),
+                    (FailureKind::Impossible, regex(r"(?i)Das geht nicht")),
+                ],
+            )),
+            endings: Some(EndingPatterns::new(
+                "de",
+                vec![
+                    (
+                        GameState::Dead,
+                        regex(r"(?i)\*{3}\s*Du bist (?:gestorben|tot)\s*\*{3}"),
+                    ),
+                    (
+                        GameState::Won,
+                        regex(r"(?i)\*{3}\s*Du hast gewonnen\s*\*{3}"),
+                    ),
+                    (
+                        GameState::Ended,
+                        regex(r"(?s)NEU ?STARTEN.{0,120}LADEN.{0,200}(?:BEENDEN|ENDE)\b"),
+                    ),
+                ],
+            )),
+            terminators: Terminators::prompt_only(),
+        }
+        .with_terminators()
+    }
+
+    /// Spanish, as used by the InformATE library
+    pub fn spanish() -> Self {
+        Self {
+            name: "es".to_string(),
+            save_verbs: verbs(&["guardar", "salvar", "save"]),
+            restore_verbs: verbs(&["cargar", "recuperar", "restore"]),
+            score_verbs: verbs(&["puntuacion", "puntuación", "puntos", "score"]),
+            inventory_verbs: verbs(&["inventario", "inv", "i", "inventory"]),
+            look_verbs: verbs(&["mirar", "m", "look"]),
+            restart_verbs: verbs(&["reiniciar", "restart"]),
+            hint_verbs: verbs(&["pista", "pistas", "hint"]),
+            quit_verbs: verbs(&["terminar", "fin", "acabar", "quit"]),
+            yes: "s".to_string(),
+            no: "n".to_string(),
+            or_word: "o".to_string(),
+            filename_prompt: regex(
+                r"Please enter a filename \[.*\]: |(?i)Introduce (?:un|el) nombre de(?:l)? (?:fichero|archivo).*: ",
+            ),
+            overwrite_prompt: regex(
+                r"(?i)Overwrite existing file\? |¿Sobrescribir (?:el )?(?:fichero|archivo)[^?]*\? ",
+            ),
+            quit_confirm: regex(r"(?i)¿Realmente quieres|¿Seguro que quieres|Are you sure"),
+            success: regex(r"Ok\.|(?i)Hecho\."),
+            failed: regex(r"(?i)Error\.|Fall[óo]|Failed\."),
+            yes_no: regex(
+                r"(?i)(?:(?:^|[.!?]\s+|\n)\s*(¿(?:quieres|deseas|realmente|seguro|estás)\b[^?.!>]*\?)|(\((?:s/n|sí/no|si/no)\)))[ \t]*(?:>[ \t]*)?\s*\z",
+            ),
+            score_formats: vec![
+                score_format(
+                    "informate",
+                    r"(?i)(?P<score>-?\d+) puntos? de (?:un total de |un máximo de )?(?P<out_of>\d+)",
+                ),
+                score_format(
+                    "moves",
+                    r"(?i)\ben (?P<moves>\d+) (?:turnos?|movimientos?)\b",
+                ),
+                score_format(
+                    "rank",
+                    r"(?i)(?:el rango|la categoría|el título) de (?P<rank>[^.!\n]+)",
+                ),
+            ],
+            failures: Some(FailurePatterns::new(
+                "es",
+                vec![
+                    (
+                        FailureKind::Disambiguation,
+                        regex(r"(?i)¿(?:Cuál|A cuál|A quién) (?:quieres decir|te refieres)"),
+                    ),
+                    (
+                        FailureKind::UnknownVerb,
+                        regex(r"(?i)Ese no es un verbo que (?:reconozca|conozca)"),
+                    ),
+                    (
+                        FailureKind::UnknownVerb,
+                        regex(r"(?i)No entiendo (?:esa|la) frase"),
+                    ),
+                    (
+                        FailureKind::UnknownVerb,
+                        regex(r"(?i)Sólo te he entendido|Solo te he entendido"),
+                    ),
+                    (
+                        FailureKind::UnknownNoun,
+                        regex(r"(?i)No conozco la palabra"),
+                    ),
+                    (FailureKind::NotVisible, regex(r"(?i)No veo (?:eso|nada)")),
+                    (FailureKind::NotVisible, regex(r"(?i)No puedes ver")),
+                    (
+                        FailureKind::NotVisible,
+                        regex(r"(?i)No hay (?:nada|ningún|ninguna)"),
+                    ),
+                    (
+                        FailureKind::Incomplete,
+                        regex(r"(?i)¿Qué (?:quieres|deseas)"),
+                    ),
+                    (FailureKind::Incomplete, regex(r"(?i)¿Perdón\?|¿Cómo\?")),
+                    (FailureKind::Impossible, regex(r"(?i)No puedes")),
+                    (FailureKind::Impossible, regex(r"(?i)No es posible")),
+                ],
+            )),
+            endings: Some(EndingPatterns::new(
+                "es",
+                vec![
+                    (GameState::Dead, regex(r"(?i)\*{3}\s*Has muerto\s*\*{3}")),
+                    (GameState::Won, regex(r"(?i)\*{3}\s*Has ganado\s*\*{3}")),
+                    (
+                        GameState::Ended,
+                        regex(
+                            r"(?s)REINICIAR.{0,120}(?:RECUPERAR|CARGAR).{0,200}(?:TERMINAR|FIN)\b",
+                        ),
+                    ),
+                ],
+            )),
+            terminators: Terminators::prompt_only(),
+        }
+        .with_terminators()
+    }
+
+    /// Rebuild `terminators` from the profile's prompts and messages
+    pub fn update_terminators(&mut self) {
+        self.terminators = Terminators::new(self);
+    }
+
+    fn with_terminators(mut self) -> Self {
+        self.update_terminators();
+        self
+    }
+
+    /// Check whether a lowercase verb is one of this language's save verbs
+    pub fn is_save_verb(&self, verb: &str) -> bool {
+        self.save_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's restore verbs
+    pub fn is_restore_verb(&self, verb: &str) -> bool {
+        self.restore_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's score verbs
+    pub fn is_score_verb(&self, verb: &str) -> bool {
+        self.score_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's inventory verbs
+    pub fn is_inventory_verb(&self, verb: &str) -> bool {
+        self.inventory_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's look verbs
+    pub fn is_look_verb(&self, verb: &str) -> bool {
+        self.look_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's restart verbs
+    pub fn is_restart_verb(&self, verb: &str) -> bool {
+        self.restart_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's hint verbs
+    pub fn is_hint_verb(&self, verb: &str) -> bool {
+        self.hint_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Check whether a lowercase verb is one of this language's quit verbs
+    pub fn is_quit_verb(&self, verb: &str) -> bool {
+        self.quit_verbs.iter().any(|v| v == verb)
+    }
+
+    /// Verb sent to the game to save
+    pub fn save_verb(&self) -> &str {
+        first(&self.save_verbs, "save")
+    }
+
+    /// Verb sent to the game to restore
+    pub fn restore_verb(&self) -> &str {
+        first(&self.restore_verbs, "restore")
+    }
+
+    /// Verb sent to the game to show the score
+    pub fn score_verb(&self) -> &str {
+        first(&self.score_verbs, "score")
+    }
+
+    /// Verb sent to the game to list the inventory
+    pub fn inventory_verb(&self) -> &str {
+        first(&self.inventory_verbs, "inventory")
+    }
+
+    /// Verb sent to the game to describe the room
+    pub fn look_verb(&self) -> &str {
+        first(&self.look_verbs, "look")
+    }
+
+    /// Verb sent to the game to restart
+    pub fn restart_verb(&self) -> &str {
+        first(&self.restart_verbs, "restart")
+    }
+
+    /// Verb sent to the game to quit
+    pub fn quit_verb(&self) -> &str {
+        first(&self.quit_verbs, "quit")
+    }
+
+    /// Find a yes/no question the game is waiting on at the end of the output
+    ///
+    /// Questions offering a choice ("... or ...?") are not yes/no questions.
+    pub fn yes_no_question(&self, output: &str) -> Option<String> {
+        let captures = self.yes_no.captures(output)?;
+        let question = match captures.get(1) {
+            Some(question) => question.as_str(),
+            // "(y/n)" markers: use the whole line as the question
+            None => output.trim_end().lines().last()?,
+        };
+        let question = question.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let choice = format!(" {} ", self.or_word);
+        if question.to_lowercase().contains(&choice) {
+            None
+        } else {
+            Some(question)
+        }
+    }
+}
+
+impl Default for LanguageProfile {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+fn verbs(verbs: &[&str]) -> Vec<String> {
+    verbs.iter().map(|v| v.to_string()).collect()
+}
+
+fn first<'a>(verbs: &'a [String], fallback: &'a str) -> &'a str {
+    verbs.first().map(String::as_str).unwrap_or(fallback)
+}
+
+fn score_format(name: &str, pattern: &str) -> ScoreFormat {
+    ScoreFormat::new(name, pattern).unwrap()
+}
+
+/// Build a regex matching any of the given patterns
+fn any_of(patterns: &[&Regex]) -> Regex {
+    let alternatives: Vec<String> = patterns
+        .iter()
+        .map(|pattern| format!("(?:{})", pattern.as_str()))
+        .collect();
+    Regex::new(&alternatives.join("|")).unwrap()
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap()
+}
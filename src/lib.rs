@@ -9,23 +9,51 @@ use std::path::PathBuf;
 
 pub mod command_result;
 pub mod commands;
+pub mod delta;
 pub mod dfrotz;
 pub mod formatters;
 pub mod gamefile;
+pub mod hints;
+pub mod inventory;
+pub mod language;
+pub mod normalize;
+pub mod patterns;
+pub mod reflow;
+pub mod room;
 pub mod savefile;
+pub mod score;
+pub mod segments;
 pub mod session;
+pub mod status;
+pub mod theme;
+pub mod transcript;
 
-pub use command_result::CommandResult;
+pub use command_result::{CommandResult, FailureKind, GameState, ReadEnd};
 pub use commands::Commands;
+pub use delta::Delta;
 pub use dfrotz::Dfrotz;
-pub use formatters::Formatters;
-pub use gamefile::Gamefile;
+pub use formatters::{FormatterRegistry, Formatters};
+pub use gamefile::{Gamefile, StoryMetadata};
+pub use hints::{HintTopic, Hints};
+pub use inventory::Item;
+pub use language::LanguageProfile;
+pub use normalize::Normalizer;
+pub use patterns::{EndingPatterns, FailurePatterns, Patterns};
+pub use reflow::Reflow;
+pub use room::Room;
 pub use savefile::Savefile;
-pub use session::Session;
+pub use score::{Score, ScoreFormat};
+pub use segments::Segments;
+pub use session::{DisambiguationPolicy, EndChoice, Session, YesNoPolicy};
+pub use theme::Theme;
+pub use transcript::{Transcript, TranscriptHeader};
 
 /// Default autosave slot name
 pub const AUTO_SAVE_SLOT: &str = "autosave";
 
+/// Prefix that sends the rest of the input to the game without interception
+pub const VERBATIM_PREFIX: &str = "!";
+
 /// Default game directory relative to project root
 pub const GAME_DIR: &str = "games";
 
@@ -38,25 +66,53 @@ lazy_static! {
     pub static ref PROMPT_REGEX: Regex =
         Regex::new(r"(?m)^>\s*$").unwrap();
 
-    /// Regex pattern for score parsing
-    pub static ref SCORE_REGEX: Regex =
-        Regex::new(r"([0-9]+) ?(?:\(total [points ]*[out ]*of [a mxiuof]*[a posible]*([0-9]+)\))?").unwrap();
-
-    /// Common failure patterns in text adventure games
-    pub static ref FAILURE_PATTERNS: Vec<Regex> = vec![
-        Regex::new(r"(?i)I don't understand").unwrap(),
-        Regex::new(r"(?i)I don't know").unwrap(),
-        Regex::new(r"(?i)You can't").unwrap(),
-        Regex::new(r"(?i)You're not").unwrap(),
-        Regex::new(r"(?i)I can't see").unwrap(),
-        Regex::new(r"(?i)That doesn't make sense").unwrap(),
-        Regex::new(r"(?i)That's not a verb I recognize").unwrap(),
-        Regex::new(r"(?i)What do you want to").unwrap(),
-        Regex::new(r"(?i)You don't see").unwrap(),
-        Regex::new(r"(?i)There is no").unwrap(),
-        Regex::new(r"(?i)I don't see").unwrap(),
-        Regex::new(r"(?i)I beg your pardon").unwrap(),
-    ];
+    /// Regex pattern for a yes/no question the game is waiting on
+    ///
+    /// Matches a trailing "Do you ...?", "Are you sure ...?" style question,
+    /// optionally followed by Infocom's "(Y is affirmative):", or a "(y/n)"
+    /// marker at the very end of the output. A `>` on the question's
+    /// own line is the interpreter reading the answer; a `>` prompt on a line
+    /// of its own means the game moved on, so the question isn't pending.
+    pub static ref YES_NO_REGEX: Regex = Regex::new(
+        r"(?i)(?:(?:^|[.!?]\s+|\n)\s*((?:do|would|will|shall|should|are|did) you\b[^?.!>]*\?)(?:[ \t]*\(y is affirmative\):)?|(\((?:y/n|yes/no|y is affirmative)\):?))[ \t]*(?:>[ \t]*)?\s*\z"
+    )
+    .unwrap();
+}
+
+/// Infocom failure patterns without their kinds, now in the `infocom` pack
+#[deprecated(note = "use `patterns::INFOCOM_PATTERNS` or `FailurePatterns::pack(\"infocom\")`")]
+pub static FAILURE_PATTERNS: Legacy<Vec<Regex>> = Legacy(|| {
+    lazy_static! {
+        static ref PATTERNS: Vec<Regex> = patterns::INFOCOM_PATTERNS
+            .iter()
+            .map(|(_, pattern)| pattern.clone())
+            .collect();
+    }
+    &PATTERNS
+});
+
+/// Regex pattern for score parsing, replaced by the score formats
+#[deprecated(note = "use `score::parse_score` or `score::SCORE_FORMATS`")]
+pub static SCORE_REGEX: Legacy<Regex> = Legacy(|| {
+    lazy_static! {
+        static ref PATTERN: Regex = Regex::new(
+            r"([0-9]+) ?(?:\(total [points ]*[out ]*of [a mxiuof]*[a posible]*([0-9]+)\))?"
+        )
+        .unwrap();
+    }
+    &PATTERN
+});
+
+/// Lazily built value behind a deprecated static, dereferencing to `T`
+#[doc(hidden)]
+pub struct Legacy<T: 'static>(fn() -> &'static T);
+
+impl<T> std::ops::Deref for Legacy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        (self.0)()
+    }
 }
 
 /// Get the default games directory
@@ -89,6 +145,42 @@ pub enum Error {
 
     #[error("Restore operation failed")]
     RestoreFailed,
+
+    #[error("Invalid story file: {0}")]
+    InvalidStoryFile(String),
+
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+
+    #[error("Unknown pattern pack '{0}', expected one of: {1}")]
+    UnknownPatternPack(String, String),
+
+    #[error("Unknown language '{0}', expected one of: {1}")]
+    UnknownLanguage(String, String),
+
+    #[error("Unknown theme '{0}', expected one of: {1}")]
+    UnknownTheme(String, String),
+
+    #[error("Unknown formatter '{0}', expected one of: {1}")]
+    UnknownFormatter(String, String),
+
+    #[error("The template formatter needs a template")]
+    MissingTemplate,
+
+    #[error("Invalid template {0}: {1}")]
+    InvalidTemplate(String, String),
+
+    #[error("Invalid hint file {0}: {1}")]
+    InvalidHintFile(String, String),
+
+    #[error("Invalid transcript {0}: {1}")]
+    InvalidTranscript(String, String),
+
+    #[error("Unsupported transcript version {0}, expected at most {1}")]
+    UnsupportedTranscriptVersion(u32, u32),
+
+    #[error("Command contains control character {0:?}")]
+    ControlCharacter(char),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,8 +1,13 @@
 //! TextPlayer CLI - Play text adventure games from the command line
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::io::{self, BufRead, Write};
-use textplayer::{Formatters, Gamefile, Session};
+use std::path::{Path, PathBuf};
+use textplayer::formatters::{FormatterOptions, HtmlFormatter, TemplateFormatter};
+use textplayer::{
+    CommandResult, Formatters, Gamefile, LanguageProfile, Normalizer, Reflow, Session, Theme,
+    Transcript,
+};
 
 #[derive(Parser)]
 #[command(name = "textplayer")]
@@ -14,13 +19,8 @@ struct Cli {
     /// Game to play (defaults to play command if no subcommand given)
     game: Option<String>,
 
-    /// Output formatter (text, data, json, shell)
-    #[arg(short, long, default_value = "shell")]
-    formatter: String,
-
-    /// Path to dfrotz executable
-    #[arg(long)]
-    dfrotz: Option<String>,
+    #[command(flatten)]
+    options: PlayOptions,
 }
 
 #[derive(Subcommand)]
@@ -30,29 +30,90 @@ enum Commands {
         /// Game file or name to play
         game: String,
 
-        /// Output formatter (text, data, json, shell)
-        #[arg(short, long, default_value = "shell")]
-        formatter: String,
-
-        /// Path to dfrotz executable
-        #[arg(long)]
-        dfrotz: Option<String>,
+        #[command(flatten)]
+        options: PlayOptions,
+    },
+    /// Print the JSON Schema for results written by the json formatter
+    Schema,
+    /// Export a transcript as a standalone HTML page
+    Export {
+        /// Transcript file written with --transcript
+        transcript: PathBuf,
+
+        /// File to write the page to, instead of standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
+/// Options for playing a game
+#[derive(Args)]
+struct PlayOptions {
+    /// Output formatter (text, data, json, ndjson, shell, markdown, html, template)
+    #[arg(short, long, default_value = "shell")]
+    formatter: String,
+
+    /// Path to dfrotz executable
+    #[arg(long)]
+    dfrotz: Option<String>,
+
+    /// Game language (en, de, es)
+    #[arg(short, long, default_value = "en")]
+    language: String,
+
+    /// Expand abbreviations the game's parser doesn't understand
+    #[arg(long)]
+    normalize: bool,
+
+    /// Seed for the interpreter's random number generator
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Write a transcript of the session to this file when the game ends
+    #[arg(long)]
+    transcript: Option<PathBuf>,
+
+    /// Join lines the interpreter wrapped and rewrap them to the terminal width
+    #[arg(long)]
+    reflow: bool,
+
+    /// Width to rewrap reflowed text at, instead of the terminal's
+    #[arg(long, requires = "reflow")]
+    width: Option<usize>,
+
+    /// Shell color theme (dark, light, plain); colors are off when not a terminal or NO_COLOR is set
+    #[arg(long, default_value = "dark")]
+    theme: String,
+
+    /// Template file for the template formatter, with {{field}} placeholders
+    #[arg(long, required_if_eq("formatter", "template"))]
+    template: Option<PathBuf>,
+}
+
 fn main() {
     let cli = Cli::parse();
 
-    // Determine game and formatter based on whether subcommand was used
-    let (game_name, formatter_name, dfrotz_path) = match cli.command {
-        Some(Commands::Play {
-            game,
-            formatter,
-            dfrotz,
-        }) => (game, formatter, dfrotz),
+    // Determine game and options based on whether subcommand was used
+    let (game_name, options) = match cli.command {
+        Some(Commands::Play { game, options }) => (game, options),
+        Some(Commands::Schema) => {
+            let schema = CommandResult::schema();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema).unwrap_or_default()
+            );
+            return;
+        }
+        Some(Commands::Export { transcript, output }) => {
+            if let Err(e) = export_html(&transcript, output.as_deref()) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
         None => {
             if let Some(game) = cli.game {
-                (game, cli.formatter, cli.dfrotz)
+                (game, cli.options)
             } else {
                 eprintln!("Error: Game name required");
                 eprintln!("Usage: textplayer [GAME] or textplayer play [GAME]");
@@ -61,17 +122,25 @@ fn main() {
         }
     };
 
-    if let Err(e) = run_game(&game_name, &formatter_name, dfrotz_path) {
+    if let Err(e) = run_game(&game_name, &options) {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 }
 
-fn run_game(
-    game_name: &str,
-    formatter_name: &str,
-    dfrotz_path: Option<String>,
-) -> textplayer::Result<()> {
+fn export_html(transcript: &Path, output: Option<&Path>) -> textplayer::Result<()> {
+    let transcript = Transcript::load(transcript)?;
+    let page = HtmlFormatter.page(&transcript);
+
+    match output {
+        Some(path) => std::fs::write(path, page)?,
+        None => io::stdout().write_all(page.as_bytes())?,
+    }
+
+    Ok(())
+}
+
+fn run_game(game_name: &str, options: &PlayOptions) -> textplayer::Result<()> {
     // Find the game file
     let gamefile = Gamefile::from_input(game_name)?;
 
@@ -80,10 +149,37 @@ fn run_game(
     }
 
     // Create session
-    let mut session = Session::new(gamefile, dfrotz_path)?;
+    let mut session = Session::new(gamefile, options.dfrotz.clone())?;
+    if let Some(e) = session.hints_error() {
+        eprintln!("Warning: playing without hints: {}", e);
+    }
+    session.set_language(LanguageProfile::by_name(&options.language)?);
+    session.set_seed(options.seed);
+    session.record_transcript(options.transcript.is_some());
+    if options.normalize {
+        let pack = session.failure_patterns().name().to_string();
+        session.set_normalizer(Some(Normalizer::for_pack(&pack)));
+    }
 
     // Get formatter
-    let formatter = Formatters::by_name(formatter_name);
+    let formatter_options = FormatterOptions {
+        session_id: session.id().to_string(),
+        game: session.gamefile().name.clone(),
+        reflow: options.reflow.then(|| {
+            let reflow = Reflow::for_terminal();
+            match options.width {
+                Some(width) => reflow.with_width(Some(width)),
+                None => reflow,
+            }
+        }),
+        theme: Some(Theme::by_name(&options.theme)?.if_supported()),
+        template: options
+            .template
+            .as_deref()
+            .map(TemplateFormatter::load)
+            .transpose()?,
+    };
+    let formatter = Formatters::by_name_with(&options.formatter, &formatter_options)?;
 
     // Setup stdin reader
     let stdin = io::stdin();
@@ -91,7 +187,7 @@ fn run_game(
     let mut stdout = io::stdout();
 
     // Run the game loop
-    session.run(|result| {
+    let last = session.run(|result| {
         // Write formatted output
         if let Err(e) = formatter.write_to(result, &mut stdout) {
             eprintln!("Output error: {}", e);
@@ -114,5 +210,20 @@ fn run_game(
         }
     })?;
 
+    // Quit at the end of input, so the final result always ends the session
+    let last = match last {
+        Some(result) => Some(result),
+        None if session.is_running() => Some(session.quit()?),
+        None => None,
+    };
+    if let Some(result) = last {
+        formatter.write_to(&result, &mut stdout)?;
+        stdout.flush()?;
+    }
+
+    if let Some(ref path) = options.transcript {
+        session.transcript().save(path)?;
+    }
+
     Ok(())
 }
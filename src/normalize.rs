@@ -0,0 +1,119 @@
+//! Normalize - Cleans up commands before they are sent to the game
+//!
+//! Older parsers only understand full words, while players and agents type
+//! "x lamp" or "NE!". A normalizer tidies casing and punctuation and expands
+//! abbreviations the game's parser doesn't know.
+
+use std::collections::HashMap;
+
+/// Abbreviations understood by Inform but not by older parsers
+const COMMON_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("x", "examine"),
+    ("l", "look"),
+    ("z", "wait"),
+    ("g", "again"),
+    ("i", "inventory"),
+    ("n", "north"),
+    ("s", "south"),
+    ("e", "east"),
+    ("w", "west"),
+    ("ne", "northeast"),
+    ("nw", "northwest"),
+    ("se", "southeast"),
+    ("sw", "southwest"),
+    ("u", "up"),
+    ("d", "down"),
+];
+
+/// Rewrites commands into a form the game's parser understands
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    /// Lowercase the command
+    pub lowercase: bool,
+    /// Remove trailing punctuation such as "?" or "!"
+    pub strip_punctuation: bool,
+    /// Words expanded when they start a command, e.g. "x" to "examine"
+    pub abbreviations: HashMap<String, String>,
+}
+
+impl Normalizer {
+    /// Create a normalizer that only tidies casing and punctuation
+    pub fn new() -> Self {
+        Self {
+            lowercase: true,
+            strip_punctuation: true,
+            abbreviations: HashMap::new(),
+        }
+    }
+
+    /// Create a normalizer for a parser family, named like the failure packs
+    ///
+    /// Infocom parsers lack "x", and Scott Adams style parsers lack most
+    /// abbreviations. Inform understands them all, so nothing is expanded.
+    pub fn for_pack(name: &str) -> Self {
+        let mut normalizer = Self::new();
+        match name {
+            "infocom" => normalizer.add_abbreviation("x", "examine"),
+            "scott-adams" => {
+                for (abbreviation, expansion) in COMMON_ABBREVIATIONS {
+                    normalizer.add_abbreviation(abbreviation, expansion);
+                }
+            }
+            _ => {}
+        }
+        normalizer
+    }
+
+    /// Expand `abbreviation` to `expansion` when it starts a command
+    pub fn add_abbreviation(&mut self, abbreviation: &str, expansion: &str) {
+        self.abbreviations
+            .insert(abbreviation.to_lowercase(), expansion.to_string());
+    }
+
+    /// Normalize a command
+    ///
+    /// Each clause of a chained command ("x lamp. n") has its first word
+    /// expanded separately.
+    pub fn normalize(&self, input: &str) -> String {
+        let mut command = input.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if self.lowercase {
+            command = command.to_lowercase();
+        }
+        if self.strip_punctuation {
+            command = command
+                .trim_end_matches(['.', '!', '?', ',', ';'])
+                .to_string();
+        }
+
+        command
+            .split(". ")
+            .map(|clause| self.expand(clause))
+            .collect::<Vec<_>>()
+            .join(". ")
+    }
+
+    fn expand(&self, clause: &str) -> String {
+        let (word, rest) = match clause.split_once(' ') {
+            Some((word, rest)) => (word, Some(rest)),
+            None => (clause, None),
+        };
+
+        let word = self
+            .abbreviations
+            .get(&word.to_lowercase())
+            .map(String::as_str)
+            .unwrap_or(word);
+
+        match rest {
+            Some(rest) => format!("{} {}", word, rest),
+            None => word.to_string(),
+        }
+    }
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,288 @@
+//! Patterns - Pattern sets used to interpret game output
+//!
+//! Parsers from different eras phrase their errors differently, so failure
+//! patterns are grouped into named packs, one per parser family. Each family
+//! also has its own way of announcing that the game is over.
+
+use crate::{
+    command_result::{FailureKind, GameState},
+    gamefile::StoryMetadata,
+    language::LanguageProfile,
+    Error, Result,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Names of the built-in failure pattern packs
+pub const PACK_NAMES: &[&str] = &["infocom", "inform6", "inform7", "scott-adams"];
+
+/// Stories whose parser can't be told from the header, by release and serial
+const KNOWN_STORIES: &[(u16, &str, &str)] = &[(1, "961111", "scott-adams")];
+
+lazy_static! {
+    /// Infocom end banners and the restart/restore/quit menu
+    static ref INFOCOM_ENDINGS: Vec<(GameState, Regex)> = vec![
+        (GameState::Dead, Regex::new(r"\*{3,}\s*You (?:have died|are dead)\s*\*{3,}").unwrap()),
+        (GameState::Won, Regex::new(r"\*{3,}\s*You have won\s*\*{3,}").unwrap()),
+        (GameState::Ended, Regex::new(r"(?i)\(Type RESTART, RESTORE,? or QUIT\)").unwrap()),
+        (GameState::Ended, Regex::new(r"(?is)\brestart\b.{0,100}\brestore\b.{0,200}(?:\bquit\b|end this session)").unwrap()),
+    ];
+
+    /// Inform library end banners and menu, shared by Inform 6 and 7
+    static ref INFORM_ENDINGS: Vec<(GameState, Regex)> = vec![
+        (GameState::Dead, Regex::new(r"\*{3}\s*You have died\s*\*{3}").unwrap()),
+        (GameState::Won, Regex::new(r"\*{3}\s*You have won\s*\*{3}").unwrap()),
+        (GameState::Ended, Regex::new(r"\*{3}\s*The End\s*\*{3}").unwrap()),
+        (GameState::Ended, Regex::new(r"Would you like to RESTART, RESTORE").unwrap()),
+    ];
+
+    /// Scott Adams style endings, which have no menu
+    static ref SCOTT_ADAMS_ENDINGS: Vec<(GameState, Regex)> = vec![
+        (GameState::Dead, Regex::new(r"(?i)I'm dead|I am dead").unwrap()),
+        (GameState::Won, Regex::new(r"(?i)I've stored all \d+ treasures").unwrap()),
+        (GameState::Ended, Regex::new(r"(?i)The game is now over").unwrap()),
+    ];
+
+    /// Infocom parser messages
+    ///
+    /// Checked in order; the first match classifies the failure, so more
+    /// specific phrases come before general ones.
+    pub static ref INFOCOM_PATTERNS: Vec<(FailureKind, Regex)> = vec![
+        (FailureKind::Disambiguation, Regex::new(r"(?i)Which (?:\w+ )?do you mean").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)That's not a verb I recognize").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I don't understand").unwrap()),
+        (FailureKind::UnknownNoun, Regex::new(r"(?i)I don't know").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)I can't see").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)You can't see").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)You don't see").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)I don't see").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)There is no").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)What do you want to").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)I beg your pardon").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You can't").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You're not").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)That doesn't make sense").unwrap()),
+    ];
+
+    /// Inform 6 library messages
+    static ref INFORM6_PATTERNS: Vec<(FailureKind, Regex)> = vec![
+        (FailureKind::Disambiguation, Regex::new(r"(?i)Which (?:\w+ )?do you mean").unwrap()),
+        (FailureKind::Disambiguation, Regex::new(r"(?i)Who do you mean").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)That's not a verb I recogni[sz]e").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I didn't understand that sentence").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I only understood you as far as").unwrap()),
+        (FailureKind::UnknownNoun, Regex::new(r"(?i)You seem to have said too much").unwrap()),
+        (FailureKind::UnknownNoun, Regex::new(r"(?i)I didn't understand the way that finished").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)You can't see any such thing").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)You can't see ").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)What do you want to").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)Whom do you want").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)I beg your pardon").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)You'll have to say which compass direction").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You can't").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You haven't got that").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)That's hardly portable").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)That's fixed in place").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You can only do that to something animate").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)That's not something you can").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You're carrying too many things already").unwrap()),
+    ];
+
+    /// Inform 7 Standard Rules messages
+    static ref INFORM7_PATTERNS: Vec<(FailureKind, Regex)> = vec![
+        (FailureKind::Disambiguation, Regex::new(r"(?i)Which (?:\w+ )?do you mean").unwrap()),
+        (FailureKind::Disambiguation, Regex::new(r"(?i)Who do you mean").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)That's not a verb I recogni[sz]e").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I didn't understand that sentence").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I only understood you as far as").unwrap()),
+        (FailureKind::UnknownNoun, Regex::new(r"(?i)That noun did not make sense in this context").unwrap()),
+        (FailureKind::UnknownNoun, Regex::new(r"(?i)You must name something more substantial").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)You can't see any such thing").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)You can't see ").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)You must supply a noun").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)What do you want to").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)Whom do you want").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)I beg your pardon").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You can't").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You aren't holding").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)That's fixed in place").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)That isn't available").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)You're carrying too many things already").unwrap()),
+    ];
+
+    /// Scott Adams style two-word parsers
+    static ref SCOTT_ADAMS_PATTERNS: Vec<(FailureKind, Regex)> = vec![
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I don't know how to").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)You use word\(s\) I don't know").unwrap()),
+        (FailureKind::UnknownVerb, Regex::new(r"(?i)I don't understand").unwrap()),
+        (FailureKind::UnknownNoun, Regex::new(r"(?i)I don't know what an? ").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)I don't see it here").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)It's not here").unwrap()),
+        (FailureKind::NotVisible, Regex::new(r"(?i)I see no").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)Give me a direction too").unwrap()),
+        (FailureKind::Incomplete, Regex::new(r"(?i)What do you want to").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)I can't go in that direction").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)I'm not carrying it").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)It's beyond my power").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)I've too much to carry").unwrap()),
+        (FailureKind::Impossible, Regex::new(r"(?i)I can't").unwrap()),
+    ];
+}
+
+/// A named, ordered set of failure patterns
+///
+/// Patterns are checked in order and the first match classifies the failure.
+/// Patterns added at runtime are checked before the pack's own patterns.
+#[derive(Debug, Clone)]
+pub struct FailurePatterns {
+    name: String,
+    patterns: Vec<(FailureKind, Regex)>,
+}
+
+impl FailurePatterns {
+    /// Create a pack from a list of patterns
+    pub fn new(name: &str, patterns: Vec<(FailureKind, Regex)>) -> Self {
+        Self {
+            name: name.to_string(),
+            patterns,
+        }
+    }
+
+    /// Load a built-in pack by name
+    pub fn pack(name: &str) -> Result<Self> {
+        let patterns = match name {
+            "infocom" => INFOCOM_PATTERNS.clone(),
+            "inform6" => INFORM6_PATTERNS.clone(),
+            "inform7" => INFORM7_PATTERNS.clone(),
+            "scott-adams" => SCOTT_ADAMS_PATTERNS.clone(),
+            _ => {
+                return Err(Error::UnknownPatternPack(
+                    name.to_string(),
+                    PACK_NAMES.join(", "),
+                ))
+            }
+        };
+
+        Ok(Self::new(name, patterns))
+    }
+
+    /// Choose a pack from the story file header
+    ///
+    /// Infocom stories carry no compiler stamp and a 1980s serial. Everything
+    /// else in the bundled games was built with Inform, apart from a few ports
+    /// of older parsers listed by release and serial.
+    ///
+    /// Inform 7 stories are compiled by Inform 6 and carry the same header
+    /// stamp, so they get the `inform6` pack. `inform7` is only used when
+    /// chosen explicitly, e.g. with `Session::set_failure_pack`.
+    pub fn for_story(metadata: &StoryMetadata) -> Self {
+        let name = KNOWN_STORIES
+            .iter()
+            .find(|(release, serial, _)| *release == metadata.release && *serial == metadata.serial)
+            .map(|(_, _, name)| *name)
+            .unwrap_or_else(|| {
+                if metadata.compiler.is_none() && metadata.serial.starts_with('8') {
+                    "infocom"
+                } else {
+                    "inform6"
+                }
+            });
+
+        Self::pack(name).unwrap_or_default()
+    }
+
+    /// Name of the pack these patterns started from
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a pattern, checked before all existing patterns
+    pub fn add(&mut self, kind: FailureKind, pattern: &str) -> Result<()> {
+        self.patterns.insert(0, (kind, Regex::new(pattern)?));
+        Ok(())
+    }
+
+    /// Classify output, returning the kind of the first matching pattern
+    pub fn classify(&self, output: &str) -> Option<FailureKind> {
+        self.patterns
+            .iter()
+            .find(|(_, pattern)| pattern.is_match(output))
+            .map(|(kind, _)| *kind)
+    }
+}
+
+impl Default for FailurePatterns {
+    fn default() -> Self {
+        Self::new("infocom", INFOCOM_PATTERNS.clone())
+    }
+}
+
+/// A named, ordered set of patterns announcing the end of the game
+///
+/// Death and victory patterns come before the end menu, so output with both a
+/// banner and a menu is classified by its banner.
+#[derive(Debug, Clone)]
+pub struct EndingPatterns {
+    name: String,
+    patterns: Vec<(GameState, Regex)>,
+}
+
+impl EndingPatterns {
+    /// Create a pack from a list of patterns
+    pub fn new(name: &str, patterns: Vec<(GameState, Regex)>) -> Self {
+        Self {
+            name: name.to_string(),
+            patterns,
+        }
+    }
+
+    /// Load a built-in pack by name, using the failure pack names
+    pub fn pack(name: &str) -> Result<Self> {
+        let patterns = match name {
+            "infocom" => INFOCOM_ENDINGS.clone(),
+            "inform6" | "inform7" => INFORM_ENDINGS.clone(),
+            "scott-adams" => SCOTT_ADAMS_ENDINGS.clone(),
+            _ => {
+                return Err(Error::UnknownPatternPack(
+                    name.to_string(),
+                    PACK_NAMES.join(", "),
+                ))
+            }
+        };
+
+        Ok(Self::new(name, patterns))
+    }
+
+    /// Name of the pack these patterns started from
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a pattern, checked before all existing patterns
+    pub fn add(&mut self, state: GameState, pattern: &str) -> Result<()> {
+        self.patterns.insert(0, (state, Regex::new(pattern)?));
+        Ok(())
+    }
+
+    /// Detect the end of the game, returning the state of the first match
+    pub fn detect(&self, output: &str) -> Option<GameState> {
+        self.patterns
+            .iter()
+            .find(|(_, pattern)| pattern.is_match(output))
+            .map(|(state, _)| *state)
+    }
+}
+
+impl Default for EndingPatterns {
+    fn default() -> Self {
+        Self::new("infocom", INFOCOM_ENDINGS.clone())
+    }
+}
+
+/// Patterns used by commands to interpret game output
+#[derive(Debug, Clone, Default)]
+pub struct Patterns {
+    pub failures: FailurePatterns,
+    pub endings: EndingPatterns,
+    pub language: LanguageProfile,
+}
@@ -0,0 +1,189 @@
+//! Reflow - Undoes the interpreter's hard wrapping of story text
+//!
+//! dfrotz breaks story text at its screen width, so a paragraph arrives as
+//! several lines split mid-sentence. Reflowing joins those lines back into
+//! paragraphs, keeping line breaks the game meant: indented lines, lists,
+//! status lines and ASCII art. Paragraphs can then be wrapped again at the
+//! width of the terminal showing them.
+
+use crate::status::STATUS_LINE_REGEX;
+
+/// Screen width dfrotz wraps at unless told otherwise
+pub const DFROTZ_WIDTH: usize = 80;
+
+/// Columns dfrotz may leave empty at the end of a wrapped line
+const WRAP_MARGIN: usize = 2;
+
+/// How to reflow story text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reflow {
+    /// Width the interpreter wrapped lines at
+    pub source_width: usize,
+    /// Width to wrap paragraphs at, or `None` to keep each on one line
+    pub width: Option<usize>,
+}
+
+impl Reflow {
+    /// Create a reflow that joins dfrotz's wrapped lines without rewrapping
+    pub fn new() -> Self {
+        Self {
+            source_width: DFROTZ_WIDTH,
+            width: None,
+        }
+    }
+
+    /// Create a reflow that rewraps to the terminal width, when it is known
+    pub fn for_terminal() -> Self {
+        Self {
+            width: terminal_width(),
+            ..Self::new()
+        }
+    }
+
+    /// Set the width to wrap paragraphs at
+    pub fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Join wrapped lines, then wrap them again if a width is set
+    pub fn apply(&self, text: &str) -> String {
+        let joined = unwrap(text, self.source_width);
+        match self.width {
+            Some(width) => wrap(&joined, width),
+            None => joined,
+        }
+    }
+}
+
+impl Default for Reflow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Terminal width from the `COLUMNS` environment variable, or the tty
+///
+/// Shells rarely export `COLUMNS`, so without it the controlling terminal
+/// is asked for its size with `stty size`. `None` when neither is known,
+/// e.g. when running without a terminal.
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| parse_width(&columns))
+        .or_else(tty_width)
+}
+
+/// Width of the controlling terminal, from `stty size`
+fn tty_width() -> Option<usize> {
+    let tty = std::fs::File::open("/dev/tty").ok()?;
+    let output = std::process::Command::new("stty")
+        .arg("size")
+        .stdin(tty)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // stty prints "rows columns"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .and_then(parse_width)
+}
+
+/// Parse a positive column count
+fn parse_width(columns: &str) -> Option<usize> {
+    columns.trim().parse().ok().filter(|columns| *columns > 0)
+}
+
+/// Join lines that were wrapped at `source_width` back into paragraphs
+///
+/// A line was wrapped when the first word of the next line wouldn't have
+/// fit on it. Lines the game laid out itself are kept as they are.
+pub fn unwrap(text: &str, source_width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut previous: Option<&str> = None;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        match (previous, lines.last_mut()) {
+            (Some(before), Some(joined)) if was_wrapped(before, line, source_width) => {
+                joined.push(' ');
+                joined.push_str(line);
+            }
+            _ => lines.push(line.to_string()),
+        }
+        previous = Some(line);
+    }
+
+    lines.join("\n")
+}
+
+/// Wrap lines longer than `width` at word boundaries
+///
+/// Laid out lines such as art and status lines are never wrapped, and
+/// indented lines keep their indentation on every wrapped line.
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+
+    for line in text.lines() {
+        if line.chars().count() <= width || is_laid_out(line) {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let mut current = indent.to_string();
+        for word in line.split_whitespace() {
+            let length = current.chars().count();
+            if length > indent.len() && length + 1 + word.chars().count() > width {
+                lines.push(std::mem::replace(&mut current, indent.to_string()));
+            }
+            if current.len() > indent.len() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        lines.push(current);
+    }
+
+    lines.join("\n")
+}
+
+/// Check if `line` was broken before `next` by the interpreter
+fn was_wrapped(line: &str, next: &str, source_width: usize) -> bool {
+    if line.trim().is_empty() || next.trim().is_empty() {
+        return false;
+    }
+    if next.starts_with(char::is_whitespace) || is_list_item(next) {
+        return false;
+    }
+    if is_laid_out(line) || is_laid_out(next) {
+        return false;
+    }
+
+    let first_word = next.split_whitespace().next().unwrap_or("");
+    line.chars().count() + 1 + first_word.chars().count() > source_width.saturating_sub(WRAP_MARGIN)
+}
+
+/// Check if a line was laid out by the game, e.g. a status line or art
+fn is_laid_out(line: &str) -> bool {
+    let text = line.trim();
+    STATUS_LINE_REGEX.is_match(line)
+        || text.contains("   ")
+        || !text.chars().any(char::is_alphanumeric)
+}
+
+/// Check if a line starts a list item, e.g. "- lamp", "* lamp" or "2. lamp"
+fn is_list_item(line: &str) -> bool {
+    let text = line.trim_start();
+    if text.starts_with("- ") || text.starts_with("* ") || text.starts_with("• ") {
+        return true;
+    }
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && matches!(text[digits..].chars().next(), Some('.' | ')'))
+}
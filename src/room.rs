@@ -0,0 +1,274 @@
+//! Room - Parses the room description printed by the game's look command
+
+use crate::{
+    reflow::{unwrap, DFROTZ_WIDTH},
+    PROMPT_REGEX,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Compass directions, in the order exits are reported
+const DIRECTIONS: &[&str] = &[
+    "north",
+    "northeast",
+    "east",
+    "southeast",
+    "south",
+    "southwest",
+    "west",
+    "northwest",
+    "up",
+    "down",
+];
+
+/// Words that make a line a sentence rather than a room name, as in
+/// "The door is locked" or "You have died"
+const SENTENCE_WORDS: &[&str] = &[
+    "i", "you", "is", "are", "was", "were", "has", "have", "had", "can", "can't", "cannot",
+    "don't", "doesn't", "won't", "isn't", "aren't", "seems", "ok", "done",
+];
+
+lazy_static! {
+    /// Status line fields that can precede the room name
+    static ref STATUS_REGEX: Regex =
+        Regex::new(r"(?i)\s{3,}(?:Score|Moves|Turns|Time)\b.*$|\s{3,}\d{1,2}:\d{2}\s*(?:AM|PM)?.*$").unwrap();
+
+    /// Objects listed in the room, e.g. "There is a small mailbox here."
+    static ref OBJECT_REGEX: Regex = Regex::new(
+        r"(?i)(?:There (?:is|are)|You can (?:also )?see) (.+?) here\b"
+    )
+    .unwrap();
+
+    /// Infocom objects placed on something, e.g. "On the table is a sack."
+    static ref PLACED_REGEX: Regex = Regex::new(
+        r"^(?:On|In|Under|Beneath|Beside|Above|Near|Inside) [^,.]+? (?:is|are|lies|sits|hangs|rests|stands) ([^,.!]+)"
+    )
+    .unwrap();
+
+    /// Infocom objects described on their own line, e.g. "A bottle is sitting on the table."
+    static ref PLACING_REGEX: Regex = Regex::new(
+        r"^(?:An?|Some|The) ([^,.!]+?) (?:is|are) (?:sitting|lying|resting|standing|hanging|here)\b"
+    )
+    .unwrap();
+
+    /// Words suggesting a sentence describes a way out
+    static ref EXIT_CUE_REGEX: Regex = Regex::new(
+        r"(?i)\b(?:exits?|leads?|leading|path|passage|way|doorway|stair|staircase|steps|go|goes|continues?|trail|corridor|hall|opening|to the)\b"
+    )
+    .unwrap();
+
+    /// Compass directions and vertical exits
+    static ref DIRECTION_REGEX: Regex = Regex::new(
+        r"(?i)\b(north(?:east|west)?|south(?:east|west)?|east|west|upwards?|upstairs|downwards?|downstairs|(?:leads?|leading|goes|go|climbs?) (?:up|down))\b"
+    )
+    .unwrap();
+}
+
+/// A room as described by the game
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Room {
+    /// Room name, from the heading line
+    pub name: String,
+    /// Description paragraph
+    pub description: String,
+    /// Objects listed as being here
+    pub objects: Vec<String>,
+    /// Exits mentioned in the description
+    pub exits: Vec<String>,
+}
+
+/// Parse a room from look output
+///
+/// The room name is the short heading line that starts the output and the
+/// description is the prose that follows it. Lines after the description
+/// list objects, either Inform style ("You can see a lamp here.") or Infocom
+/// style ("On the table is a sack.", "A bottle is sitting on the table.",
+/// and the indented contents under "The bottle contains:").
+pub fn parse_room(output: &str) -> Option<Room> {
+    let output = PROMPT_REGEX.replace_all(output, "");
+    let mut paragraphs = output
+        .split("\n\n")
+        .map(|paragraph| paragraph.trim_matches('\n'))
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .peekable();
+
+    // A status line on its own is followed by the room heading
+    if let Some(first) = paragraphs.peek() {
+        if !first.trim().contains('\n') && STATUS_REGEX.is_match(first) {
+            paragraphs.next();
+        }
+    }
+
+    let first = paragraphs.next()?;
+    let mut lines = first.lines().filter(|line| !line.trim().is_empty());
+
+    let heading = STATUS_REGEX
+        .replace(lines.next()?.trim(), "")
+        .trim()
+        .to_string();
+    if !is_room_name(&heading) {
+        return None;
+    }
+
+    // Joining the interpreter's wrapped lines leaves one line per sentence
+    // group, so object listings start lines of their own
+    let rest = unwrap(&lines.collect::<Vec<_>>().join("\n"), DFROTZ_WIDTH);
+    let rest: Vec<&str> = rest.lines().collect();
+    let split = rest
+        .iter()
+        .enumerate()
+        .position(|(index, line)| is_object_line(line, index == 0))
+        .unwrap_or(rest.len());
+    let mut listings: Vec<&str> = rest[split..].to_vec();
+    listings.extend(paragraphs);
+
+    // Some games put a blank line between the heading and the description
+    let description = if rest.is_empty() && !listings.is_empty() {
+        listings.remove(0).to_string()
+    } else {
+        rest[..split].join("\n")
+    };
+
+    let description = unwrap_lines(&description);
+    let listings = unwrap(&listings.join("\n"), DFROTZ_WIDTH);
+
+    let mut objects = parse_objects(&format!("{} {}", description, unwrap_lines(&listings)));
+    for object in parse_object_lines(&listings) {
+        if !objects.contains(&object) {
+            objects.push(object);
+        }
+    }
+
+    Some(Room {
+        name: heading,
+        objects,
+        exits: parse_exits(&description),
+        description,
+    })
+}
+
+/// Check if a line lists objects rather than describing the room
+///
+/// The first line only counts when it's an Inform style listing, since a
+/// description may well open with "In the corner is a desk."
+fn is_object_line(line: &str, first: bool) -> bool {
+    let line = line.trim();
+    if OBJECT_REGEX.find(line).is_some_and(|m| m.start() == 0) {
+        return true;
+    }
+    !first
+        && (PLACED_REGEX.is_match(line)
+            || PLACING_REGEX.is_match(line)
+            || line.ends_with("contains:"))
+}
+
+/// Find objects in Infocom style listing lines
+fn parse_object_lines(listings: &str) -> Vec<String> {
+    let mut objects = Vec::new();
+    let mut in_container = false;
+
+    for line in listings.lines() {
+        let text = line.trim();
+        let indented = line.starts_with(char::is_whitespace);
+
+        let object = if in_container && indented {
+            Some(text.trim_end_matches('.'))
+        } else if let Some(captures) = PLACED_REGEX.captures(text) {
+            Some(captures.get(1).map_or("", |m| m.as_str()))
+        } else {
+            PLACING_REGEX
+                .captures(text)
+                .map(|captures| captures.get(1).map_or("", |m| m.as_str()))
+        };
+        in_container = text.ends_with("contains:") || (in_container && indented);
+
+        if let Some(object) = object.map(strip_article) {
+            if !object.is_empty() && !objects.iter().any(|o| o == object) {
+                objects.push(object.to_string());
+            }
+        }
+    }
+
+    objects
+}
+
+/// Remove a leading article, in any case
+fn strip_article(text: &str) -> &str {
+    let text = text.trim();
+    ["a ", "an ", "the ", "some "]
+        .iter()
+        .find_map(|article| {
+            text.get(..article.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(article))
+                .map(|_| &text[article.len()..])
+        })
+        .unwrap_or(text)
+        .trim()
+}
+
+/// Room headings are short and aren't sentences
+fn is_room_name(line: &str) -> bool {
+    !line.is_empty()
+        && line.len() < 60
+        && !line.ends_with(['.', '!', '?', ':'])
+        && !line.starts_with('>')
+        && line.chars().next().is_some_and(|c| c.is_uppercase())
+        && !line
+            .split_whitespace()
+            .any(|word| SENTENCE_WORDS.contains(&word.to_lowercase().as_str()))
+}
+
+/// Join hard-wrapped lines into a single line of prose
+fn unwrap_lines(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Find objects listed as being in the room
+fn parse_objects(text: &str) -> Vec<String> {
+    let mut objects = Vec::new();
+
+    for captures in OBJECT_REGEX.captures_iter(text) {
+        let list = captures[1].replace(", and ", ", ").replace(" and ", ", ");
+        for object in list.split(", ") {
+            let object = strip_article(object);
+
+            if !object.is_empty() && !objects.iter().any(|o| o == object) {
+                objects.push(object.to_string());
+            }
+        }
+    }
+
+    objects
+}
+
+/// Find exits mentioned in sentences that describe ways out
+fn parse_exits(description: &str) -> Vec<String> {
+    let mut found = Vec::new();
+
+    for sentence in description.split_inclusive(['.', '!', '?', ';']) {
+        if !EXIT_CUE_REGEX.is_match(sentence) {
+            continue;
+        }
+
+        for captures in DIRECTION_REGEX.captures_iter(sentence) {
+            let word = captures[1].to_lowercase();
+            let direction = if word.starts_with("up") || word.ends_with(" up") {
+                "up"
+            } else if word.starts_with("down") || word.ends_with(" down") {
+                "down"
+            } else {
+                word.as_str()
+            };
+            if !found.iter().any(|d| d == direction) {
+                found.push(direction.to_string());
+            }
+        }
+    }
+
+    DIRECTIONS
+        .iter()
+        .filter(|direction| found.iter().any(|d| d == *direction))
+        .map(|direction| direction.to_string())
+        .collect()
+}
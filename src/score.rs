@@ -0,0 +1,142 @@
+//! Score - Parses the output of the game's score command
+//!
+//! Each parser family words its score differently, so output is matched
+//! against a list of score formats. A format is a regex with any of the
+//! named groups `score`, `out_of`, `moves` and `rank`.
+
+use crate::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// Score formats used by English games
+    pub static ref SCORE_FORMATS: Vec<ScoreFormat> = vec![
+        // Infocom: "Your score is 10 (total of 350 points), in 34 moves."
+        format(
+            "infocom",
+            r"(?i)Your score (?:is|would be|was) (?P<score>-?\d+) \(total (?:of )?(?P<out_of>\d+)(?: points)?\)(?:,? in (?P<moves>\d+) (?:moves?|turns?))?",
+        ),
+        // Infocom: "Your score is 0 of a possible 400, in 3 turns."
+        format(
+            "infocom-possible",
+            r"(?i)Your score (?:is|would be|was) (?P<score>-?\d+) (?:points? )?(?:out )?of (?:a )?(?:possible |maximum )?(?P<out_of>\d+)(?:,? in (?P<moves>\d+) (?:moves?|turns?))?",
+        ),
+        // "In 34 turns you have scored 10 out of 350"
+        format(
+            "turns-first",
+            r"(?i)In (?P<moves>\d+) (?:turns?|moves?),? you (?:have )?scored (?P<score>-?\d+)(?: points?)? (?:out )?of (?:a possible )?(?P<out_of>\d+)",
+        ),
+        // Inform: "You have so far scored 10 out of a possible 350, in 34 turns"
+        format(
+            "inform",
+            r"(?i)(?:You have so far scored|You have scored|you scored|You scored) (?P<score>-?\d+)(?: points?)? out of (?:a possible |a maximum of )?(?P<out_of>\d+)(?:,? in (?P<moves>\d+) (?:turns?|moves?))?",
+        ),
+        // Scott Adams: "I've stored 2 treasures. On a scale of 0 to 100 that rates a 16."
+        format(
+            "scott-adams",
+            r"(?i)On a scale of 0 to (?P<out_of>\d+),? that rates (?:a )?(?P<score>-?\d+)",
+        ),
+        // Status line: "Score: 10  Moves: 34"
+        format(
+            "status",
+            r"(?i)Score:\s*(?P<score>-?\d+)(?:\s*/\s*(?P<out_of>\d+))?(?:\s+(?:Moves|Turns):\s*(?P<moves>\d+))?",
+        ),
+        // "Your score is 10"
+        format(
+            "plain",
+            r"(?i)Your score (?:is|would be|was) (?P<score>-?\d+)\b",
+        ),
+        // "You have 10 points", but not "You have 3 treasures"
+        format("points", r"(?i)You have (?P<score>-?\d+) points?\b"),
+        format("moves", r"(?i)\bin (?P<moves>\d+) (?:turns?|moves?)\b"),
+        format(
+            "rank",
+            r"(?i)(?:gives you the rank of|earning you the rank of|earns you the rank of|you are ranked(?: as)?)\s+(?:an? )?(?P<rank>[^.!\n]+)",
+        ),
+    ];
+}
+
+fn format(name: &str, pattern: &str) -> ScoreFormat {
+    ScoreFormat::new(name, pattern).unwrap()
+}
+
+/// A regex recognizing one way of printing the score
+#[derive(Debug, Clone)]
+pub struct ScoreFormat {
+    pub name: String,
+    pub pattern: Regex,
+}
+
+impl ScoreFormat {
+    /// Create a format from a regex with `score`, `out_of`, `moves` or `rank` groups
+    pub fn new(name: &str, pattern: &str) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern)?,
+        })
+    }
+}
+
+/// Score information reported by the game
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    pub score: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_of: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moves: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<String>,
+}
+
+/// Parse score output using the English score formats
+pub fn parse_score(output: &str) -> Option<Score> {
+    parse_score_with(output, &SCORE_FORMATS)
+}
+
+/// Parse score output using the given formats
+///
+/// Each field is taken from the first format that matches and captures it,
+/// so formats for the rank or move count alone can follow the score formats.
+/// Hard-wrapped lines are joined before matching.
+pub fn parse_score_with(output: &str, formats: &[ScoreFormat]) -> Option<Score> {
+    let text = output.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let mut score = None;
+    let mut out_of = None;
+    let mut moves = None;
+    let mut rank = None;
+
+    for format in formats {
+        let Some(captures) = format.pattern.captures(&text) else {
+            continue;
+        };
+        let number = |name: &str| captures.name(name)?.as_str().parse::<i32>().ok();
+
+        if score.is_none() {
+            score = number("score");
+            // Keep out_of and moves with the score they were printed with
+            if score.is_some() {
+                out_of = number("out_of");
+                moves = number("moves").or(moves);
+            }
+        }
+        if moves.is_none() {
+            moves = number("moves");
+        }
+        if rank.is_none() {
+            rank = captures
+                .name("rank")
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|r| !r.is_empty());
+        }
+    }
+
+    Some(Score {
+        score: score?,
+        out_of,
+        moves,
+        rank,
+    })
+}
@@ -0,0 +1,107 @@
+//! Segments - Splits interpreter output into its parts
+//!
+//! Raw output can hold an echo of the command, a status line, the story text
+//! and the trailing `>` prompt. Splitting it once here means every formatter
+//! strips the prompt and status line the same way.
+
+use crate::{status::STATUS_LINE_REGEX, PROMPT_REGEX};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The parts of a command's output
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Segments {
+    /// The command, when the interpreter echoed it back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub echo: Option<String>,
+    /// Status line with the location, score, moves or time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Story text, without the echo, status line or prompts
+    #[serde(default)]
+    pub body: String,
+    /// The prompt the game is waiting at, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+impl Segments {
+    /// Split raw output from the command `input`
+    ///
+    /// The echo and status line are only recognized before the story text.
+    /// Every prompt is removed from the body, since merged results can
+    /// contain more than one.
+    pub fn split(raw_output: &str, input: &str) -> Self {
+        let mut segments = Self::default();
+        let mut lines = raw_output.lines().peekable();
+
+        while lines.peek().is_some_and(|line| line.trim().is_empty()) {
+            lines.next();
+        }
+
+        let command = input.trim();
+        if !command.is_empty() {
+            if let Some(line) = lines.peek() {
+                let echoed = line.trim().trim_start_matches('>').trim();
+                if echoed.eq_ignore_ascii_case(command) {
+                    segments.echo = Some(echoed.to_string());
+                    lines.next();
+                }
+            }
+        }
+
+        while lines.peek().is_some_and(|line| line.trim().is_empty()) {
+            lines.next();
+        }
+
+        if let Some(line) = lines.peek() {
+            if STATUS_LINE_REGEX.is_match(line) {
+                segments.status = Some(line.trim().to_string());
+                lines.next();
+            }
+        }
+
+        let mut body = Vec::new();
+        for line in lines {
+            if PROMPT_REGEX.is_match(line) {
+                segments.prompt = Some(line.trim().to_string());
+            } else {
+                // Output after an answered question starts on the prompt's line
+                let line = line.strip_prefix('>').map_or(line, str::trim_start);
+                // A prompt followed by more story isn't one the game waits at
+                if !line.trim().is_empty() {
+                    segments.prompt = None;
+                }
+                body.push(line.trim_end());
+            }
+        }
+
+        segments.body = tidy(&body);
+        segments
+    }
+
+    /// Check if the output ended at a prompt
+    pub fn has_prompt(&self) -> bool {
+        self.prompt.is_some()
+    }
+}
+
+/// Join body lines, dropping surrounding blank lines and runs of blank lines
+fn tidy(lines: &[&str]) -> String {
+    let mut text = String::new();
+    let mut blank_lines = 0;
+
+    for line in lines {
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !text.is_empty() {
+            text.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        text.push_str(line);
+        blank_lines = 0;
+    }
+
+    text
+}
@@ -1,42 +1,242 @@
 //! Session - Manages game session lifecycle and output formatting
 
 use crate::{
-    command_result::{CommandResult, Operation},
+    command_result::{CommandResult, GameState, Operation},
     commands::{
-        Command, Commands, QuitCommand, RestoreCommand, SaveCommand, ScoreCommand, StartCommand,
+        ActionCommand, Command, Commands, InventoryCommand, LookCommand, QuitCommand,
+        RestartCommand, RestoreCommand, SaveCommand, ScoreCommand, StartCommand,
     },
+    delta::Tracker,
     dfrotz::Dfrotz,
     gamefile::Gamefile,
+    hints::Hints,
+    language::LanguageProfile,
+    normalize::Normalizer,
+    patterns::{EndingPatterns, FailurePatterns, Patterns},
     savefile::Savefile,
-    Result,
+    status::fill_status,
+    transcript::{Transcript, TranscriptHeader},
+    Error, FailureKind, Result, VERBATIM_PREFIX,
 };
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How `Session` answers yes/no questions asked by the game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum YesNoPolicy {
+    /// Return the question to the caller, who answers with the next command
+    #[default]
+    Ask,
+    /// Answer every question with "yes"
+    AlwaysYes,
+    /// Answer every question with "no"
+    AlwaysNo,
+}
+
+/// How `Session` answers "Which do you mean ...?" questions from the parser
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisambiguationPolicy {
+    /// Return the question to the caller, who answers with `Session::disambiguate`
+    #[default]
+    Ask,
+    /// Answer with the first candidate the parser offered
+    First,
+}
+
+/// What to do once the game is over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndChoice {
+    /// Start the game again from the beginning
+    Restart,
+    /// Restore a saved game from a slot
+    Restore(Option<String>),
+    /// Leave the game
+    Quit,
+}
 
 /// Mid-level: Manages game session lifecycle
 pub struct Session {
+    id: String,
     gamefile: Gamefile,
     game: Dfrotz,
     started: bool,
     start_result: Option<CommandResult>,
+    passthrough_verbs: Vec<String>,
+    yes_no_policy: YesNoPolicy,
+    disambiguation_policy: DisambiguationPolicy,
+    candidates: Vec<String>,
+    patterns: Patterns,
+    normalizer: Option<Normalizer>,
+    game_state: GameState,
+    hints: Option<Hints>,
+    hints_error: Option<Error>,
+    revealed: HashMap<String, usize>,
+    room: Option<String>,
+    turn: u64,
+    tracker: Tracker,
+    recording: bool,
+    results: Vec<CommandResult>,
+    /// Interpreter version banner, asked for once when the game starts
+    interpreter: Option<String>,
 }
 
 impl Session {
     /// Create a new game session
+    ///
+    /// Hints are loaded from the game's hint file when there is one. A hint
+    /// file that can't be loaded doesn't stop the game; the session plays
+    /// without hints and `hints_error` says why.
     pub fn new(gamefile: Gamefile, dfrotz_path: Option<String>) -> Result<Self> {
         let game = Dfrotz::new(gamefile.full_path()?, dfrotz_path)?;
+        let hints_path = gamefile.hints_path();
+        let (hints, hints_error) = if hints_path.is_file() {
+            match Hints::load(&hints_path) {
+                Ok(hints) => (Some(hints), None),
+                Err(e) => (None, Some(e)),
+            }
+        } else {
+            (None, None)
+        };
+        let failures = gamefile
+            .metadata()
+            .map(|metadata| FailurePatterns::for_story(&metadata))
+            .unwrap_or_default();
+        let endings = EndingPatterns::pack(failures.name()).unwrap_or_default();
 
         Ok(Self {
+            id: new_session_id(),
             gamefile,
             game,
             started: false,
             start_result: None,
+            passthrough_verbs: Vec::new(),
+            yes_no_policy: YesNoPolicy::default(),
+            disambiguation_policy: DisambiguationPolicy::default(),
+            candidates: Vec::new(),
+            patterns: Patterns {
+                failures,
+                endings,
+                language: LanguageProfile::default(),
+            },
+            normalizer: None,
+            game_state: GameState::default(),
+            hints,
+            hints_error,
+            revealed: HashMap::new(),
+            room: None,
+            turn: 0,
+            tracker: Tracker::default(),
+            recording: false,
+            results: Vec::new(),
+            interpreter: None,
         })
     }
 
+    /// Identifier for this session, unique across runs
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Game file being played
+    pub fn gamefile(&self) -> &Gamefile {
+        &self.gamefile
+    }
+
+    /// Why the game's hint file couldn't be loaded, if it couldn't
+    pub fn hints_error(&self) -> Option<&Error> {
+        self.hints_error.as_ref()
+    }
+
+    /// Set verbs that this game uses as story verbs
+    ///
+    /// Commands starting with one of these verbs are sent straight to the game
+    /// instead of being intercepted, e.g. `save` in a game about saving a princess.
+    pub fn set_passthrough_verbs(&mut self, verbs: Vec<String>) {
+        self.passthrough_verbs = verbs;
+    }
+
+    /// Set how yes/no questions from the game are answered
+    pub fn set_yes_no_policy(&mut self, policy: YesNoPolicy) {
+        self.yes_no_policy = policy;
+    }
+
+    /// Set how disambiguation questions from the parser are answered
+    pub fn set_disambiguation_policy(&mut self, policy: DisambiguationPolicy) {
+        self.disambiguation_policy = policy;
+    }
+
+    /// Use a built-in failure pattern pack instead of the one chosen for the story
+    ///
+    /// The end-of-game patterns of the same parser family come with it.
+    pub fn set_failure_pack(&mut self, name: &str) -> Result<()> {
+        self.patterns.failures = FailurePatterns::pack(name)?;
+        self.patterns.endings = EndingPatterns::pack(name)?;
+        Ok(())
+    }
+
+    /// Add a failure pattern, checked before the pack's own patterns
+    pub fn add_failure_pattern(&mut self, kind: FailureKind, pattern: &str) -> Result<()> {
+        self.patterns.failures.add(kind, pattern)
+    }
+
+    /// Failure patterns used to classify action output
+    pub fn failure_patterns(&self) -> &FailurePatterns {
+        &self.patterns.failures
+    }
+
+    /// Add a pattern announcing the end of the game, e.g. a custom death banner
+    pub fn add_ending_pattern(&mut self, state: GameState, pattern: &str) -> Result<()> {
+        self.patterns.endings.add(state, pattern)
+    }
+
+    /// Use a language profile for system verbs, prompts and patterns
+    ///
+    /// Profiles that carry their own failure or ending patterns replace the
+    /// current pack.
+    pub fn set_language(&mut self, language: LanguageProfile) {
+        if let Some(ref failures) = language.failures {
+            self.patterns.failures = failures.clone();
+        }
+        if let Some(ref endings) = language.endings {
+            self.patterns.endings = endings.clone();
+        }
+        self.patterns.language = language;
+    }
+
+    /// Keep every result for `transcript`
+    ///
+    /// Recording is off by default, since a long session would otherwise
+    /// hold every result in memory. Results recorded so far are kept when
+    /// recording is turned off; `take_transcript` drains them.
+    pub fn record_transcript(&mut self, record: bool) {
+        self.recording = record;
+    }
+
+    /// Seed the interpreter's random number generator, for repeatable games
+    ///
+    /// Must be set before the game starts.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.game.set_seed(seed);
+    }
+
+    /// Normalize commands before they are sent to the game
+    ///
+    /// Normalization is off by default. `Normalizer::for_pack` builds one
+    /// suited to the parser family of the current failure pack.
+    pub fn set_normalizer(&mut self, normalizer: Option<Normalizer>) {
+        self.normalizer = normalizer;
+    }
+
     /// Run the game with a closure that processes results
     ///
     /// The closure receives the result and should return the next command.
     /// Return None to exit the game loop.
-    pub fn run<F>(&mut self, mut handler: F) -> Result<()>
+    ///
+    /// When the game stops, e.g. after quitting, the loop ends without asking
+    /// the closure for another command, and the final result is returned.
+    /// It is `None` when the closure ended the loop.
+    pub fn run<F>(&mut self, mut handler: F) -> Result<Option<CommandResult>>
     where
         F: FnMut(&CommandResult) -> Option<String>,
     {
@@ -44,13 +244,23 @@ impl Session {
 
         while self.is_running() {
             if let Some(command) = handler(&result) {
-                result = self.call(&command)?;
+                result = match self.call(&command) {
+                    // Keep the loop alive so interactive players can retype
+                    Err(e @ Error::ControlCharacter(_)) => CommandResult::new(
+                        command,
+                        String::new(),
+                        Operation::Error,
+                        false,
+                        Some(e.to_string()),
+                    ),
+                    other => other?,
+                };
             } else {
-                break;
+                return Ok(None);
             }
         }
 
-        Ok(())
+        Ok(Some(result))
     }
 
     /// Start the game
@@ -61,6 +271,7 @@ impl Session {
 
         self.game.start()?;
         self.started = true;
+        self.interpreter = self.game.version();
 
         let start_command = StartCommand;
         let result = self.execute_command(&start_command)?;
@@ -74,14 +285,85 @@ impl Session {
         self.started && self.game.is_running()
     }
 
+    /// State of the game after the last command
+    pub fn game_state(&self) -> GameState {
+        self.game_state
+    }
+
     /// Execute a command
     ///
     /// We intentionally intercept certain commands for security and convenience:
     /// - save/restore commands are restricted to the saves directory
     /// - quit is intercepted to ensure clean shutdown
+    ///
+    /// Prefix a command with `!` to send it to the game verbatim.
+    ///
+    /// When the game has a hint file, `hint` and `hint <topic>` are answered
+    /// from it without reaching the game.
+    ///
+    /// Commands containing newlines or other control characters are rejected
+    /// with `Error::ControlCharacter`. Each line would otherwise reach the
+    /// interpreter as a separate command, bypassing interception.
     pub fn call(&mut self, cmd: &str) -> Result<CommandResult> {
-        let command = Commands::create(cmd, Some(&self.gamefile.name));
-        self.execute_command(command.as_ref())
+        reject_control_characters(cmd)?;
+
+        let normalized = match self.normalizer {
+            Some(ref normalizer) if !cmd.trim().starts_with(VERBATIM_PREFIX) => {
+                normalizer.normalize(cmd)
+            }
+            _ => cmd.to_string(),
+        };
+
+        if self.hints.is_some() {
+            if let Some(query) = hint_query(
+                &normalized,
+                &self.passthrough_verbs,
+                &self.patterns.language,
+            ) {
+                let topic = Some(query).filter(|query| !query.is_empty());
+                let result = self.hint(topic.as_deref())?;
+                return Ok(self.keep_input(result, cmd, &normalized));
+            }
+        }
+
+        let command = Commands::create_with(
+            &normalized,
+            Some(&self.gamefile.name),
+            &self.passthrough_verbs,
+            &self.patterns.language,
+        );
+        let result = self.execute_command(command.as_ref())?;
+        let result = self.keep_input(result, cmd, &normalized);
+
+        let result = self.answer_yes_no_question(result)?;
+        self.resolve_disambiguation(result)
+    }
+
+    /// Answer the parser's last "Which do you mean ...?" question
+    ///
+    /// The choice is usually one of the result's `candidates`, but any text the
+    /// game accepts as an answer can be sent.
+    pub fn disambiguate(&mut self, choice: &str) -> Result<CommandResult> {
+        reject_control_characters(choice)?;
+
+        if self.candidates.is_empty() {
+            return Ok(CommandResult::new(
+                choice.to_string(),
+                String::new(),
+                Operation::Error,
+                false,
+                Some("No disambiguation question pending".to_string()),
+            ));
+        }
+
+        let command = ActionCommand {
+            input: choice.to_string(),
+        };
+        let result = self.execute_command(&command)?;
+        let result = self.answer_yes_no_question(result)?;
+        self.candidates = candidates(&result);
+
+        Ok(result)
     }
 
     /// Get the current score
@@ -90,8 +372,21 @@ impl Session {
         self.execute_command(&command)
     }
 
+    /// List the items the player is carrying
+    pub fn inventory(&mut self) -> Result<CommandResult> {
+        let command = InventoryCommand;
+        self.execute_command(&command)
+    }
+
+    /// Describe the current room
+    pub fn look(&mut self) -> Result<CommandResult> {
+        let command = LookCommand;
+        self.execute_command(&command)
+    }
+
     /// Save the game to a slot
     pub fn save(&mut self, slot: Option<String>) -> Result<CommandResult> {
+        reject_control_characters(slot.as_deref().unwrap_or_default())?;
         let savefile = Savefile::new(Some(self.gamefile.name.clone()), slot);
         let command = SaveCommand { savefile };
         self.execute_command(&command)
@@ -99,30 +394,382 @@ impl Session {
 
     /// Restore the game from a slot
     pub fn restore(&mut self, slot: Option<String>) -> Result<CommandResult> {
+        reject_control_characters(slot.as_deref().unwrap_or_default())?;
         let savefile = Savefile::new(Some(self.gamefile.name.clone()), slot);
         let command = RestoreCommand { savefile };
         self.execute_command(&command)
     }
 
+    /// Transcript of the session so far
+    ///
+    /// Holds every exchange recorded with `record_transcript` in order,
+    /// including automatic answers that were merged into the result returned
+    /// to the caller.
+    pub fn transcript(&self) -> Transcript {
+        Transcript::new(self.transcript_header(), self.results.clone())
+    }
+
+    /// Transcript of the results recorded since the last call, removing them
+    ///
+    /// Long-running sessions can call this periodically to write results out
+    /// without keeping them in memory.
+    pub fn take_transcript(&mut self) -> Transcript {
+        Transcript::new(self.transcript_header(), std::mem::take(&mut self.results))
+    }
+
+    fn transcript_header(&self) -> TranscriptHeader {
+        let mut options = BTreeMap::new();
+        options.insert("language".to_string(), self.patterns.language.name.clone());
+        options.insert(
+            "failure_pack".to_string(),
+            self.patterns.failures.name().to_string(),
+        );
+        options.insert(
+            "normalize".to_string(),
+            self.normalizer.is_some().to_string(),
+        );
+        options.insert(
+            "yes_no_policy".to_string(),
+            format!("{:?}", self.yes_no_policy),
+        );
+        options.insert(
+            "disambiguation_policy".to_string(),
+            format!("{:?}", self.disambiguation_policy),
+        );
+        if !self.passthrough_verbs.is_empty() {
+            options.insert(
+                "passthrough_verbs".to_string(),
+                self.passthrough_verbs.join(" "),
+            );
+        }
+
+        TranscriptHeader {
+            game: self.gamefile.name.clone(),
+            story: self.gamefile.metadata().ok(),
+            interpreter: self.interpreter.clone(),
+            seed: self.game.seed(),
+            options,
+        }
+    }
+
+    /// Get the next hint for a topic, or list the topics for the current room
+    ///
+    /// Each request for the same topic reveals one more hint, and the result's
+    /// `hints` detail holds every hint revealed so far.
+    pub fn hint(&mut self, topic: Option<&str>) -> Result<CommandResult> {
+        let clock = Instant::now();
+        let mut result = self.lookup_hint(topic);
+        self.stamp(&mut result, clock);
+        self.record(&result);
+        Ok(result)
+    }
+
+    /// Answer a hint request from the hint file
+    fn lookup_hint(&mut self, topic: Option<&str>) -> CommandResult {
+        let input = match topic {
+            Some(topic) => format!("hint {}", topic),
+            None => "hint".to_string(),
+        };
+        let hint_result = |success: bool, message: String| {
+            CommandResult::new(
+                input.clone(),
+                String::new(),
+                Operation::Hint,
+                success,
+                Some(message),
+            )
+        };
+
+        let Some(ref hints) = self.hints else {
+            return hint_result(false, "No hints available for this game".to_string());
+        };
+        let room = self.room.as_deref();
+
+        let Some(query) = topic else {
+            let topics: Vec<&str> = hints
+                .for_room(room)
+                .iter()
+                .map(|topic| topic.topic.as_str())
+                .collect();
+            let mut result = hint_result(
+                !topics.is_empty(),
+                format!("Hint topics:\n{}", topics.join("\n")),
+            );
+            result.add_detail("topics".to_string(), serde_json::json!(topics));
+            return result;
+        };
+
+        let Some(found) = hints.find(query, room) else {
+            return hint_result(false, format!("No hint topic matches '{}'", query));
+        };
+
+        let revealed = self.revealed.entry(found.topic.clone()).or_insert(0);
+        if *revealed < found.hints.len() {
+            *revealed += 1;
+        }
+        let shown = &found.hints[..*revealed];
+
+        let mut result = hint_result(!shown.is_empty(), shown.last().cloned().unwrap_or_default());
+        result.add_detail("topic".to_string(), serde_json::json!(found.topic));
+        result.add_detail("hints".to_string(), serde_json::json!(shown));
+        result.add_detail(
+            "remaining".to_string(),
+            serde_json::json!(found.hints.len() - shown.len()),
+        );
+
+        result
+    }
+
+    /// Restart the game from the beginning
+    pub fn restart(&mut self) -> Result<CommandResult> {
+        let command = RestartCommand;
+        self.execute_command(&command)
+    }
+
+    /// Answer the game's end menu with a restart, restore or quit
+    ///
+    /// The menu is only shown once the game is over, but the same choices
+    /// work mid-game too.
+    pub fn choose_ending(&mut self, choice: EndChoice) -> Result<CommandResult> {
+        match choice {
+            EndChoice::Restart => self.restart(),
+            EndChoice::Restore(slot) => self.restore(slot),
+            EndChoice::Quit => self.quit(),
+        }
+    }
+
     /// Quit the game
     pub fn quit(&mut self) -> Result<CommandResult> {
         let command = QuitCommand;
         self.execute_command(&command)
     }
 
-    /// Execute a command
+    /// Answer a pending yes/no question according to the policy
+    ///
+    /// The answer's output is appended to the question so the caller sees the
+    /// whole exchange as one result.
+    fn answer_yes_no_question(&mut self, result: CommandResult) -> Result<CommandResult> {
+        let answer = match self.yes_no_policy {
+            YesNoPolicy::Ask => return Ok(result),
+            YesNoPolicy::AlwaysYes => self.patterns.language.yes.clone(),
+            YesNoPolicy::AlwaysNo => self.patterns.language.no.clone(),
+        };
+
+        // Questions in the end menu are left for `choose_ending`
+        if !result.is_action_command() || !result.is_yes_no_question() || result.is_game_over() {
+            return Ok(result);
+        }
+
+        let command = ActionCommand {
+            input: answer.clone(),
+        };
+        let mut reply = self.execute_command(&command)?;
+        merge_turns(&mut reply, &result);
+        reply.input = result.input;
+        reply.sent = result.sent;
+        reply.prepend_output(&result.raw_output);
+        reply.add_detail("answered".to_string(), serde_json::json!(answer));
+
+        Ok(reply)
+    }
+
+    /// Remember the parser's candidates and answer according to the policy
+    ///
+    /// An automatic answer is only given once, so a parser that keeps asking
+    /// returns its question to the caller.
+    fn resolve_disambiguation(&mut self, result: CommandResult) -> Result<CommandResult> {
+        self.candidates = candidates(&result);
+
+        let choice = match (self.disambiguation_policy, self.candidates.first()) {
+            (DisambiguationPolicy::First, Some(choice)) => choice.clone(),
+            _ => return Ok(result),
+        };
+
+        let mut reply = self.disambiguate(&choice)?;
+        merge_turns(&mut reply, &result);
+        reply.input = result.input;
+        reply.sent = result.sent;
+        reply.prepend_output(&result.raw_output);
+        reply.add_detail("chosen".to_string(), serde_json::json!(choice));
+
+        Ok(reply)
+    }
+
+    /// Execute a command and record the game state it leaves behind
     fn execute_command(&mut self, command: &dyn Command) -> Result<CommandResult> {
-        if self.is_running() {
-            command.execute(&mut self.game)
+        let clock = Instant::now();
+        self.game.take_read_end();
+
+        let mut result = if self.is_running() {
+            command.execute(&mut self.game, &self.patterns)?
         } else {
-            Ok(CommandResult::new(
+            CommandResult::new(
                 command.input(),
                 String::new(),
                 Operation::Error,
                 false,
                 Some("Game not running".to_string()),
-            ))
+            )
+        };
+
+        self.stamp(&mut result, clock);
+        result.read_end = self.game.take_read_end();
+        self.game_state = self.next_game_state(&result);
+        result.game_state = self.game_state;
+
+        fill_status(&mut result);
+        if result.operation != Operation::Error {
+            self.track_changes(&mut result);
         }
+
+        // Remember where the player is, to offer hints for the room
+        if matches!(
+            result.operation,
+            Operation::Start | Operation::Action | Operation::Look
+        ) {
+            if let Some(ref location) = result.location {
+                self.room = Some(location.clone());
+            }
+        }
+
+        self.record(&result);
+
+        Ok(result)
+    }
+
+    /// Give a result the next turn number and its timing
+    fn stamp(&mut self, result: &mut CommandResult, clock: Instant) {
+        let elapsed = clock.elapsed();
+
+        self.turn += 1;
+        result.turn = self.turn;
+        result.started_at = unix_millis(SystemTime::now() - elapsed);
+        result.elapsed_ms = elapsed.as_millis() as u64;
+    }
+
+    /// Attach what changed since the previous turn
+    fn track_changes(&mut self, result: &mut CommandResult) {
+        let delta = self.tracker.track(result);
+        result.delta = Some(delta).filter(|delta| !delta.is_empty());
+    }
+
+    /// Keep a copy of a result for the transcript, when recording
+    fn record(&mut self, result: &CommandResult) {
+        if self.recording {
+            self.results.push(result.clone());
+        }
+    }
+
+    /// Give a result the command as typed, and the normalized command when it differs
+    fn keep_input(
+        &mut self,
+        mut result: CommandResult,
+        cmd: &str,
+        normalized: &str,
+    ) -> CommandResult {
+        if result.input != cmd || normalized != cmd {
+            result.input = cmd.to_string();
+            result.sent = Some(normalized.to_string()).filter(|normalized| normalized != cmd);
+            self.amend_recorded(&result);
+        }
+        result
+    }
+
+    /// Replace the recorded copy of a result that was changed after execution
+    fn amend_recorded(&mut self, result: &CommandResult) {
+        if let Some(recorded) = self
+            .results
+            .iter_mut()
+            .rev()
+            .find(|recorded| recorded.turn == result.turn)
+        {
+            *recorded = result.clone();
+        }
+    }
+
+    /// Work out the game state after a command
+    ///
+    /// An end menu shown again after a death or victory keeps that state, and
+    /// output without an ending means play has resumed, e.g. after UNDO.
+    fn next_game_state(&self, result: &CommandResult) -> GameState {
+        match result.operation {
+            Operation::Start | Operation::Action => {
+                match self.patterns.endings.detect(&result.raw_output) {
+                    Some(GameState::Ended) if self.game_state.is_over() => self.game_state,
+                    Some(state) => state,
+                    None => GameState::Playing,
+                }
+            }
+            Operation::Restart | Operation::Restore if result.success => GameState::Playing,
+            Operation::Quit => GameState::Ended,
+            _ => self.game_state,
+        }
+    }
+}
+
+/// Candidates offered by a disambiguation question in the result
+fn candidates(result: &CommandResult) -> Vec<String> {
+    result
+        .get_detail("candidates")
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Make a merged reply's timing and delta cover the result it answered
+fn merge_turns(reply: &mut CommandResult, result: &CommandResult) {
+    let finished_at = reply.started_at + reply.elapsed_ms;
+    reply.started_at = result.started_at;
+    reply.elapsed_ms = finished_at.saturating_sub(result.started_at);
+
+    let delta = match (result.delta.clone(), reply.delta.take()) {
+        (Some(earlier), Some(later)) => Some(earlier.then(later)),
+        (earlier, later) => earlier.or(later),
+    };
+    reply.delta = delta.filter(|delta| !delta.is_empty());
+}
+
+/// Session id from the start time, process id and a per-process counter
+fn new_session_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_nanos();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    format!("{:x}-{:x}-{:x}", nanos, std::process::id(), count)
+}
+
+/// Milliseconds since the Unix epoch
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+/// Topic asked about when the input is a hint request
+///
+/// Returns an empty topic for a bare `hint`, and `None` for other input.
+fn hint_query(input: &str, passthrough: &[String], language: &LanguageProfile) -> Option<String> {
+    let (verb, rest) = match input.trim().split_once(' ') {
+        Some((verb, rest)) => (verb, rest.trim()),
+        None => (input.trim(), ""),
+    };
+    let verb = verb.to_lowercase();
+
+    if passthrough.iter().any(|p| p.to_lowercase() == verb) || !language.is_hint_verb(&verb) {
+        return None;
+    }
+
+    Some(rest.to_string())
+}
+
+/// Reject input that would reach the interpreter as more than one line
+fn reject_control_characters(input: &str) -> Result<()> {
+    match input.chars().find(|c| c.is_control()) {
+        Some(c) => Err(Error::ControlCharacter(c)),
+        None => Ok(()),
     }
 }
 
@@ -0,0 +1,159 @@
+//! Status - Extracts status line fields from game output
+//!
+//! Many games print a status line with the location, score, moves or time
+//! of day above each response, e.g.
+//! " Canyon Bottom                    Score: 0        Moves: 26".
+
+use crate::{
+    command_result::{CommandResult, Operation},
+    room::parse_room,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    pub static ref SCORE_PATTERN: Regex = Regex::new(r"(?i)Score:\s*(-?\d+)").unwrap();
+    pub static ref MOVES_PATTERN: Regex = Regex::new(r"(?i)Moves:\s*(\d+)").unwrap();
+    pub static ref TIME_PATTERN: Regex = Regex::new(r"(\d{1,2}:\d{2}\s*(?:AM|PM))").unwrap();
+
+    /// A whole status line, with its fields spaced out from the location
+    pub static ref STATUS_LINE_REGEX: Regex = Regex::new(
+        r"(?i)\s{3,}(?:Score|Moves|Turns):\s*-?\d+|\s{3,}\d{1,2}:\d{2}\s*(?:AM|PM)?\s*$"
+    )
+    .unwrap();
+}
+
+/// Fields found in a status line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    pub location: Option<String>,
+    pub score: Option<i32>,
+    pub moves: Option<i32>,
+    pub time: Option<String>,
+}
+
+/// Extract status line fields from output
+pub fn parse_status(text: &str) -> Status {
+    Status {
+        location: extract_location(text),
+        score: extract_score(text),
+        moves: extract_moves(text),
+        time: extract_time(text),
+    }
+}
+
+/// Fill the result's state fields the command didn't set from the output
+///
+/// Fields come from the status line. Without one, the location comes from
+/// a room heading followed by its description; a reply that merely starts
+/// with a short line, like "Ok" or "The door is locked", has no location.
+pub fn fill_status(result: &mut CommandResult) {
+    let status = result
+        .segments
+        .status
+        .as_deref()
+        .map(parse_status)
+        .unwrap_or_default();
+
+    if result.location.is_none() {
+        result.location = status.location.or_else(|| {
+            if !matches!(result.operation, Operation::Start | Operation::Action) {
+                return None;
+            }
+            parse_room(&result.segments.body)
+                .filter(|room| !room.description.is_empty())
+                .map(|room| room.name)
+        });
+    }
+    result.score = result.score.or(status.score);
+    result.moves = result.moves.or(status.moves);
+    result.time = result.time.take().or(status.time);
+}
+
+/// Location from the first line of output
+pub fn extract_location(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let first_line = lines.first()?.trim();
+
+    if first_line.is_empty() {
+        return None;
+    }
+
+    // Try different location extraction strategies
+    let location = extract_location_with_stats(first_line)
+        .or_else(|| extract_standalone_location(first_line))?;
+
+    if valid_location(&location) {
+        Some(location)
+    } else {
+        None
+    }
+}
+
+fn extract_location_with_stats(line: &str) -> Option<String> {
+    // Handle formats like: " Canyon Bottom                    Score: 0        Moves: 26"
+    let parts: Vec<&str> = line.split("   ").collect();
+    if parts.len() >= 2 {
+        let candidate = parts[0].trim();
+        if !candidate.is_empty() {
+            return Some(candidate.to_string());
+        }
+    }
+    None
+}
+
+fn extract_standalone_location(line: &str) -> Option<String> {
+    let candidate = line.trim();
+
+    // Only consider it a location if it doesn't contain stats and isn't an error message
+    if !candidate.contains("Score")
+        && !candidate.contains("Moves")
+        && !candidate.contains("AM")
+        && !candidate.contains("PM")
+        && !candidate.contains(":")
+        && !candidate.ends_with('.')
+        && !candidate.ends_with('!')
+        && !candidate.ends_with('?')
+        && candidate.len() < 50
+        && !candidate.to_lowercase().contains("response")
+    {
+        Some(candidate.to_string())
+    } else {
+        None
+    }
+}
+
+/// Score from a "Score: 10" status field
+pub fn extract_score(text: &str) -> Option<i32> {
+    SCORE_PATTERN
+        .captures(text)
+        .and_then(|cap| cap.get(1))
+        .and_then(|m| m.as_str().parse().ok())
+}
+
+/// Moves from a "Moves: 26" status field
+pub fn extract_moves(text: &str) -> Option<i32> {
+    MOVES_PATTERN
+        .captures(text)
+        .and_then(|cap| cap.get(1))
+        .and_then(|m| m.as_str().parse().ok())
+}
+
+/// Time of day from a "10:30 AM" status field
+pub fn extract_time(text: &str) -> Option<String> {
+    TIME_PATTERN
+        .captures(text)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+fn valid_location(location: &str) -> bool {
+    !location.is_empty()
+        && !location.starts_with("I don't ")
+        && !location.starts_with("I can't ")
+        && !location.starts_with("What do you ")
+        && !location.starts_with("You're ")
+        && !location.starts_with("You ")
+        && !location.starts_with("That's not ")
+        && !location.starts_with("I beg your pardon")
+}
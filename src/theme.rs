@@ -0,0 +1,121 @@
+//! Theme - Colors used by the shell formatter
+//!
+//! Colors are ANSI SGR parameters such as "32" for green or "1;34" for bold
+//! blue. An empty color leaves that part of the output unstyled.
+
+use crate::{Error, Result};
+use std::ffi::OsStr;
+use std::io::IsTerminal;
+
+/// Names accepted by `Theme::by_name`
+pub const THEME_NAMES: &[&str] = &["dark", "light", "plain"];
+
+/// Colors for each part of the shell output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Prompt after a successful command
+    pub prompt: String,
+    /// Marker for successful system operations
+    pub success: String,
+    /// Marker for failed operations, and the prompt after a failed command
+    pub failure: String,
+    /// System feedback text, e.g. "SAVE: Game saved"
+    pub feedback: String,
+    /// Room name heading the story text
+    pub room: String,
+}
+
+impl Theme {
+    /// Get a built-in theme by name
+    pub fn by_name(name: &str) -> Result<Self> {
+        match name {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            "plain" => Ok(Self::plain()),
+            _ => Err(Error::UnknownTheme(
+                name.to_string(),
+                THEME_NAMES.join(", "),
+            )),
+        }
+    }
+
+    /// Bright colors for dark terminal backgrounds
+    pub fn dark() -> Self {
+        Self {
+            prompt: "32".to_string(),
+            success: "32".to_string(),
+            failure: "31".to_string(),
+            feedback: "36".to_string(),
+            room: "1;33".to_string(),
+        }
+    }
+
+    /// Deeper colors that stay readable on light terminal backgrounds
+    pub fn light() -> Self {
+        Self {
+            prompt: "34".to_string(),
+            success: "32".to_string(),
+            failure: "31".to_string(),
+            feedback: "35".to_string(),
+            room: "1;34".to_string(),
+        }
+    }
+
+    /// No colors at all
+    pub fn plain() -> Self {
+        Self {
+            prompt: String::new(),
+            success: String::new(),
+            failure: String::new(),
+            feedback: String::new(),
+            room: String::new(),
+        }
+    }
+
+    /// Default theme for standard output
+    ///
+    /// Plain when standard output isn't a terminal or `NO_COLOR` is set.
+    pub fn for_stdout() -> Self {
+        Self::dark().if_supported()
+    }
+
+    /// This theme, or the plain theme when standard output can't show colors
+    pub fn if_supported(self) -> Self {
+        if colors_supported() {
+            self
+        } else {
+            Self::plain()
+        }
+    }
+
+    /// Wrap `text` in the escape codes for `color`
+    pub fn paint(color: &str, text: &str) -> String {
+        if color.is_empty() || text.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::for_stdout()
+    }
+}
+
+/// Check if standard output is a terminal and `NO_COLOR` isn't set
+///
+/// See <https://no-color.org>: any non-empty `NO_COLOR` disables colors.
+pub fn colors_supported() -> bool {
+    colors_allowed(
+        std::env::var_os("NO_COLOR").as_deref(),
+        std::io::stdout().is_terminal(),
+    )
+}
+
+/// Check if colors may be used, given `NO_COLOR` and whether the output is a terminal
+pub fn colors_allowed(no_color: Option<&OsStr>, terminal: bool) -> bool {
+    let no_color = no_color.is_some_and(|value| !value.is_empty());
+    !no_color && terminal
+}
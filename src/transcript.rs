@@ -0,0 +1,78 @@
+//! Transcript - A whole session as a versioned log file
+//!
+//! A transcript holds a header identifying the game and how it was played,
+//! followed by every result in the order the session produced them.
+
+use crate::{command_result::CommandResult, gamefile::StoryMetadata, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the transcript format written by this crate
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// What was played, and how
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptHeader {
+    /// Game file name
+    pub game: String,
+    /// Story file identity from its header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub story: Option<StoryMetadata>,
+    /// Version banner of the interpreter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+    /// Random number seed given to the interpreter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Session options, e.g. language and failure pack
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+}
+
+/// A header and the results of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    /// Format version, `TRANSCRIPT_VERSION` when written by this crate
+    pub version: u32,
+    pub header: TranscriptHeader,
+    /// Results in the order they were produced
+    pub results: Vec<CommandResult>,
+}
+
+impl Transcript {
+    /// Create a transcript in the current format version
+    pub fn new(header: TranscriptHeader, results: Vec<CommandResult>) -> Self {
+        Self {
+            version: TRANSCRIPT_VERSION,
+            header,
+            results,
+        }
+    }
+
+    /// Load a transcript from a JSON file
+    ///
+    /// Transcripts written by a newer version of the format are rejected.
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let transcript: Self = serde_json::from_str(&json)
+            .map_err(|e| Error::InvalidTranscript(path.display().to_string(), e.to_string()))?;
+
+        if transcript.version > TRANSCRIPT_VERSION {
+            return Err(Error::UnsupportedTranscriptVersion(
+                transcript.version,
+                TRANSCRIPT_VERSION,
+            ));
+        }
+
+        Ok(transcript)
+    }
+
+    /// Save the transcript as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::InvalidTranscript(path.display().to_string(), e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
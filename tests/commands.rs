@@ -0,0 +1,93 @@
+use textplayer::command_result::Operation;
+use textplayer::commands::{parse_disambiguation, parse_save_restore};
+use textplayer::{Commands, LanguageProfile};
+
+fn operation(input: &str) -> Operation {
+    Commands::create(input, Some("zork1")).operation()
+}
+
+fn slot(input: &str) -> Option<String> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    parse_save_restore(&tokens, Some("zork1")).map(|savefile| savefile.slot)
+}
+
+#[test]
+fn bare_save_uses_the_default_slot() {
+    assert_eq!(operation("save"), Operation::Save);
+    assert_eq!(operation("SAVE"), Operation::Save);
+    assert_eq!(slot("save"), Some(textplayer::AUTO_SAVE_SLOT.to_string()));
+}
+
+#[test]
+fn explicit_slot_is_intercepted() {
+    assert_eq!(operation("save slot=dragon"), Operation::Save);
+    assert_eq!(operation("restore Slot=dragon"), Operation::Restore);
+    assert_eq!(slot("save slot=dragon"), Some("dragon".to_string()));
+}
+
+#[test]
+fn named_save_and_restore_go_to_the_game() {
+    assert_eq!(operation("save princess"), Operation::Action);
+    assert_eq!(operation("restore power"), Operation::Action);
+    assert_eq!(operation("restore power to the grid"), Operation::Action);
+}
+
+#[test]
+fn named_restore_ignores_existing_save_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+
+    assert_eq!(slot("restore dragon"), None);
+    std::fs::create_dir("saves").unwrap();
+    std::fs::write("saves/zork1_dragon.qzl", b"").unwrap();
+    assert_eq!(slot("restore dragon"), None);
+    assert_eq!(operation("restore dragon"), Operation::Action);
+}
+
+#[test]
+fn verbatim_prefix_sends_input_to_the_game() {
+    let command = Commands::create("!score", Some("zork1"));
+    assert_eq!(command.operation(), Operation::Action);
+    assert_eq!(command.input(), "score");
+
+    assert_eq!(
+        Commands::create("! save princess", None).input(),
+        "save princess"
+    );
+}
+
+#[test]
+fn verbatim_bare_save_and_restore_stay_intercepted() {
+    assert_eq!(operation("!save"), Operation::Save);
+    assert_eq!(operation("! RESTORE"), Operation::Restore);
+}
+
+#[test]
+fn passthrough_verbs_go_to_the_game() {
+    let passthrough = vec!["save".to_string()];
+    let command = Commands::create_with("save", None, &passthrough, &LanguageProfile::default());
+
+    assert_eq!(command.operation(), Operation::Action);
+}
+
+#[test]
+fn disambiguation_candidates() {
+    assert_eq!(
+        parse_disambiguation("Which lamp do you mean, the brass lantern or the broken lantern?"),
+        Some(vec![
+            "brass lantern".to_string(),
+            "broken lantern".to_string()
+        ])
+    );
+    assert_eq!(
+        parse_disambiguation(
+            "Which do you mean, the red ball, the blue ball, or a green\nball?\n\n>"
+        ),
+        Some(vec![
+            "red ball".to_string(),
+            "blue ball".to_string(),
+            "green ball".to_string()
+        ])
+    );
+    assert_eq!(parse_disambiguation("Taken.\n\n>"), None);
+}
@@ -0,0 +1,103 @@
+//! Fake interpreter for tests that drive a whole session
+//!
+//! The fake is a shell script answering each line of input with a canned
+//! reply, and logging the lines it receives so tests can check what reached
+//! the interpreter.
+
+#![allow(dead_code)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use textplayer::{Gamefile, Session};
+
+/// Reply to input the fake has no canned reply for
+pub const UNKNOWN_REPLY: &str = "I don't know the word \"xyzzy\".\n\n>";
+
+/// Session playing zork1 against a fake interpreter
+pub struct FakeGame {
+    pub session: Session,
+    dir: TempDir,
+}
+
+impl FakeGame {
+    /// Fake that prints `opening`, then answers each input from `replies`
+    ///
+    /// Replies are matched against whole input lines, and should end with a
+    /// prompt unless the game is meant to wait.
+    pub fn new(opening: &str, replies: &[(&str, &str)]) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cases = String::new();
+        for (index, (input, reply)) in replies.iter().enumerate() {
+            let file = dir.path().join(format!("reply{}.txt", index));
+            std::fs::write(&file, reply).unwrap();
+            cases.push_str(&format!(
+                "    {}) cat {} ;;\n",
+                quote(input),
+                quote(&file.display().to_string())
+            ));
+        }
+
+        let opening_file = dir.path().join("opening.txt");
+        std::fs::write(&opening_file, opening).unwrap();
+        let unknown_file = dir.path().join("unknown.txt");
+        std::fs::write(&unknown_file, UNKNOWN_REPLY).unwrap();
+        let log = dir.path().join("input.log");
+
+        let script = format!(
+            "#!/bin/sh\n\
+             if [ \"$1\" = \"-v\" ]; then echo -v >> {versions}; echo 'FROTZ V2.54 (fake)'; exit 0; fi\n\
+             cat {opening}\n\
+             while IFS= read -r line; do\n\
+             printf '%s\\n' \"$line\" >> {log}\n\
+             case \"$line\" in\n\
+             {cases}    *) cat {unknown} ;;\n\
+             esac\n\
+             done\n",
+            opening = quote(&opening_file.display().to_string()),
+            log = quote(&log.display().to_string()),
+            unknown = quote(&unknown_file.display().to_string()),
+            versions = quote(&dir.path().join("versions.log").display().to_string()),
+        );
+        let path = dir.path().join("dfrotz");
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let session = Session::new(zork1(), Some(path.display().to_string())).unwrap();
+        Self { session, dir }
+    }
+
+    /// Lines the fake interpreter received so far
+    pub fn received(&self) -> Vec<String> {
+        std::fs::read_to_string(self.dir.path().join("input.log"))
+            .map(|log| log.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Number of times the interpreter was asked for its version
+    pub fn version_queries(&self) -> usize {
+        std::fs::read_to_string(self.dir.path().join("versions.log"))
+            .map(|log| log.lines().count())
+            .unwrap_or(0)
+    }
+
+    /// Directory holding the fake's files
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+/// Zork I from the bundled games
+pub fn zork1() -> Gamefile {
+    Gamefile::new("zork1".to_string(), games_dir().join("zork1.z5"))
+}
+
+/// Directory of the bundled games
+pub fn games_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("games")
+}
+
+/// Quote a string for the shell
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
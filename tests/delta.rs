@@ -0,0 +1,52 @@
+use textplayer::command_result::Operation;
+use textplayer::delta::Tracker;
+use textplayer::status::fill_status;
+use textplayer::CommandResult;
+
+fn action(input: &str, output: &str) -> CommandResult {
+    let mut result = CommandResult::new(
+        input.to_string(),
+        output.to_string(),
+        Operation::Action,
+        true,
+        None,
+    );
+    fill_status(&mut result);
+    result
+}
+
+#[test]
+fn room_heading_moves_the_player() {
+    let mut tracker = Tracker::default();
+    tracker.track(&action(
+        "",
+        "West of House\nYou are standing in an open field west of a white house.\n\n>",
+    ));
+
+    let delta = tracker.track(&action(
+        "north",
+        "North of House\nYou are facing the north side of a white house.\n\n>",
+    ));
+
+    assert_eq!(delta.location.as_deref(), Some("North of House"));
+    assert_eq!(delta.previous_location.as_deref(), Some("West of House"));
+    assert!(delta.new_room);
+}
+
+#[test]
+fn one_line_reply_has_no_location_delta() {
+    let mut tracker = Tracker::default();
+    tracker.track(&action(
+        "",
+        "West of House\nYou are standing in an open field west of a white house.\n\n>",
+    ));
+
+    for (input, output) in [
+        ("wait", "Ok\n\n>"),
+        ("open door", "The door is locked\n\n>"),
+        ("jump", "Done\n\n>"),
+    ] {
+        let delta = tracker.track(&action(input, output));
+        assert!(delta.is_empty(), "{:?} gave {:?}", output, delta);
+    }
+}
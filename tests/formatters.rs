@@ -0,0 +1,298 @@
+use textplayer::command_result::Operation;
+use textplayer::formatters::{
+    Event, Formatter, HtmlFormatter, MarkdownFormatter, NdjsonFormatter, TemplateFormatter,
+};
+use textplayer::{CommandResult, Error, Transcript, TranscriptHeader};
+
+fn action(input: &str, output: &str) -> CommandResult {
+    CommandResult::new(
+        input.to_string(),
+        output.to_string(),
+        Operation::Action,
+        true,
+        None,
+    )
+}
+
+#[test]
+fn markdown_escapes_room_text() {
+    let result = action(
+        "read sign",
+        "Hall of *Stars* #3\nThe | sign reads: <Bob & Alice>\n\n>",
+    );
+
+    assert_eq!(
+        MarkdownFormatter.format(&result),
+        "**> read sign**\n\n\
+         Hall of \\*Stars\\* \\#3\n\
+         The \\| sign reads: \\<Bob \\& Alice\\>\n\n"
+    );
+}
+
+#[test]
+fn markdown_escapes_underlines() {
+    let result = action("look", "West of House\n=============\nA field.\n---\n\n>");
+
+    assert_eq!(
+        MarkdownFormatter.format(&result),
+        "**> look**\n\nWest of House\n\\=============\nA field.\n\\---\n\n"
+    );
+}
+
+#[test]
+fn markdown_escapes_list_markers() {
+    let result = action("read list", "- milk\n+ eggs\n1. bread\n\n>");
+
+    assert_eq!(
+        MarkdownFormatter.format(&result),
+        "**> read list**\n\n\\- milk\n\\+ eggs\n1\\. bread\n\n"
+    );
+}
+
+#[test]
+fn markdown_escapes_feedback() {
+    let result = CommandResult::new(
+        "save".to_string(),
+        String::new(),
+        Operation::Save,
+        true,
+        Some("[my_*slot*] Game saved successfully".to_string()),
+    );
+
+    assert_eq!(
+        MarkdownFormatter.format(&result),
+        "> **Save:** \\[my\\_\\*slot\\*\\] Game saved successfully\n\n"
+    );
+}
+
+#[test]
+fn html_escapes_fragments() {
+    let result = action(
+        "say \"hi\" & <wave>",
+        "The troll's <b>axe</b> & \"club\"\n\n>",
+    );
+
+    assert_eq!(
+        HtmlFormatter.format(&result),
+        "<section class=\"turn action success\">\n\
+         <p class=\"command\"><kbd>say &quot;hi&quot; &amp; &lt;wave&gt;</kbd></p>\n\
+         <div class=\"output\">\n\
+         <p>The troll&#39;s &lt;b&gt;axe&lt;/b&gt; &amp; &quot;club&quot;</p>\n\
+         </div>\n</section>\n"
+    );
+}
+
+#[test]
+fn html_escapes_feedback() {
+    let result = CommandResult::new(
+        "save".to_string(),
+        String::new(),
+        Operation::Save,
+        false,
+        Some("Can't write <saves/a&b.qzl>".to_string()),
+    );
+
+    assert_eq!(
+        HtmlFormatter.format(&result),
+        "<aside class=\"feedback save failure\"><strong>Save</strong> \
+         Can&#39;t write &lt;saves/a&amp;b.qzl&gt;</aside>\n"
+    );
+}
+
+#[test]
+fn html_page_escapes_header_and_turns() {
+    let header = TranscriptHeader {
+        game: "<zork>&1.z5".to_string(),
+        interpreter: Some("FROTZ \"2.54\"".to_string()),
+        ..TranscriptHeader::default()
+    };
+    let transcript = Transcript::new(header, vec![action("x <lamp>", "It's a \"lamp\".\n\n>")]);
+
+    let page = HtmlFormatter.page(&transcript);
+
+    assert!(page.contains("<title>&lt;zork&gt;&amp;1</title>"));
+    assert!(page.contains("<h1>&lt;zork&gt;&amp;1</h1>"));
+    assert!(page.contains("<dd>FROTZ &quot;2.54&quot;</dd>"));
+    assert!(page.contains("<kbd>x &lt;lamp&gt;</kbd>"));
+    assert!(page.contains("<p>It&#39;s a &quot;lamp&quot;.</p>"));
+    assert!(!page.contains("<zork>"));
+}
+
+#[test]
+fn html_keeps_laid_out_text_preformatted() {
+    let result = action("i", "You are carrying:\n  A <lamp>\n\n>");
+
+    assert!(HtmlFormatter
+        .format(&result)
+        .contains("<pre>You are carrying:\n  A &lt;lamp&gt;</pre>"));
+}
+
+fn ndjson() -> NdjsonFormatter {
+    NdjsonFormatter {
+        session_id: "session-1".to_string(),
+        game: "zork1".to_string(),
+    }
+}
+
+fn event(operation: Operation) -> Event {
+    let result = CommandResult::new(String::new(), String::new(), operation, true, None);
+    Event::for_result(&result)
+}
+
+#[test]
+fn ndjson_events() {
+    assert_eq!(event(Operation::Start), Event::Start);
+    for operation in [
+        Operation::Action,
+        Operation::Look,
+        Operation::Inventory,
+        Operation::Score,
+    ] {
+        assert_eq!(event(operation), Event::Turn, "{:?}", operation);
+    }
+    for operation in [Operation::Save, Operation::Restore, Operation::Hint] {
+        assert_eq!(event(operation), Event::System, "{:?}", operation);
+    }
+    assert_eq!(event(Operation::Quit), Event::End);
+}
+
+#[test]
+fn ndjson_writes_one_event_per_line() {
+    let formatter = ndjson();
+    let mut results = [
+        action("", "West of House\nYou are standing in an open field.\n\n>"),
+        action("look", "West of House\nThere is a small mailbox here.\n\n>"),
+        CommandResult::new(
+            "quit".to_string(),
+            "Your score is 0.\n".to_string(),
+            Operation::Quit,
+            true,
+            Some("Game ended".to_string()),
+        ),
+    ];
+    results[0].operation = Operation::Start;
+    for (turn, result) in results.iter_mut().enumerate() {
+        result.turn = turn as u64;
+    }
+
+    let stream: String = results
+        .iter()
+        .map(|result| formatter.format(result))
+        .collect();
+    let lines: Vec<serde_json::Value> = stream
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(lines.len(), 3);
+    let events: Vec<&str> = lines
+        .iter()
+        .map(|line| line["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(events, vec!["start", "turn", "end"]);
+    assert_eq!(lines[1]["session_id"], "session-1");
+    assert_eq!(lines[1]["turn"], 1);
+    assert_eq!(lines[1]["game"], "zork1");
+    assert_eq!(lines[1]["result"]["input"], "look");
+}
+
+fn render(template: &str, result: &CommandResult) -> String {
+    TemplateFormatter::new(template).unwrap().format(result)
+}
+
+fn template_error(template: &str) -> String {
+    match TemplateFormatter::new(template) {
+        Err(Error::InvalidTemplate(source, reason)) => {
+            assert_eq!(source, "(inline)");
+            reason
+        }
+        other => panic!(
+            "expected an invalid template error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+fn template_fills_fields() {
+    let mut result = action(
+        "open mailbox",
+        "Opening the small mailbox reveals a leaflet.\n\n>",
+    );
+    result.details.insert("hint".to_string(), "Read it".into());
+
+    assert_eq!(
+        render(
+            "{{operation}} {{input}}: {{segments.body}} ({{details.hint}})",
+            &result
+        ),
+        "action open mailbox: Opening the small mailbox reveals a leaflet. (Read it)"
+    );
+}
+
+#[test]
+fn template_falls_back_and_leaves_missing_values_empty() {
+    let result = action("wait", "Time passes.\n\n>");
+
+    assert_eq!(
+        render("{{message | segments.body}}", &result),
+        "Time passes."
+    );
+    assert_eq!(render("[{{message}}]", &result), "[]");
+    assert_eq!(render("[{{details.hint}}]", &result), "[]");
+}
+
+#[test]
+fn template_rejects_unknown_fields() {
+    assert_eq!(
+        template_error("{{inputs}}"),
+        "unknown field 'inputs' in {{inputs}}"
+    );
+    assert_eq!(
+        template_error("{{message | mesage}}"),
+        "unknown field 'mesage' in {{message | mesage}}"
+    );
+}
+
+#[test]
+fn template_rejects_unclosed_and_empty_placeholders() {
+    assert_eq!(
+        template_error("> {{input"),
+        "unclosed placeholder at byte 2"
+    );
+    assert_eq!(template_error("{{ }}"), "empty field name in {{ }}");
+}
+
+#[test]
+fn template_keeps_single_braces() {
+    let result = action("look", "West of House\n\n>");
+    assert_eq!(
+        render("{ \"input\": \"{{input}}\" }", &result),
+        "{ \"input\": \"look\" }"
+    );
+    assert_eq!(render("{input", &result), "{input");
+}
+
+#[test]
+fn template_escapes_braces() {
+    let result = action("look", "West of House\n\n>");
+    assert_eq!(
+        render("\\{{input}} is {{input}}", &result),
+        "{{input}} is look"
+    );
+}
+
+#[test]
+fn template_load_reports_the_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("turn.tpl");
+    std::fs::write(&path, "{{nope}}").unwrap();
+
+    match TemplateFormatter::load(&path) {
+        Err(Error::InvalidTemplate(source, _)) => assert_eq!(source, path.display().to_string()),
+        other => panic!(
+            "expected an invalid template error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::Path;
+use textplayer::{Error, Hints};
+
+fn zork1() -> Hints {
+    Hints::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("games/zork1.hints.json")).unwrap()
+}
+
+fn topics(hints: &Hints, room: Option<&str>) -> Vec<String> {
+    hints
+        .for_room(room)
+        .iter()
+        .map(|topic| topic.topic.clone())
+        .collect()
+}
+
+#[test]
+fn room_topics_come_first() {
+    let hints = zork1();
+
+    assert_eq!(
+        topics(&hints, Some("the troll room")),
+        vec![
+            "How do I get past the troll?",
+            "What do I do with the leaflet?",
+            "Where do the treasures go?",
+        ]
+    );
+    assert_eq!(
+        topics(&hints, None),
+        vec![
+            "What do I do with the leaflet?",
+            "Where do the treasures go?"
+        ]
+    );
+}
+
+#[test]
+fn find_matches_any_part_of_a_topic() {
+    let hints = zork1();
+
+    let topic = hints.find("TROLL", None).unwrap();
+    assert_eq!(topic.topic, "How do I get past the troll?");
+    assert_eq!(topic.hints.len(), 3);
+
+    assert_eq!(
+        hints
+            .find("white house", Some("Behind House"))
+            .unwrap()
+            .topic,
+        "How do I get into the white house?"
+    );
+}
+
+#[test]
+fn find_without_a_match() {
+    assert!(zork1().find("grue", Some("Kitchen")).is_none());
+}
+
+#[test]
+fn invalid_hint_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("broken.hints.json");
+    std::fs::write(&path, "{\"topics\": [{\"topic\": 1}]}").unwrap();
+
+    assert!(matches!(
+        Hints::load(&path),
+        Err(Error::InvalidHintFile(..))
+    ));
+}
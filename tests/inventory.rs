@@ -0,0 +1,93 @@
+use textplayer::inventory::parse_inventory;
+use textplayer::Item;
+
+fn item(name: &str) -> Item {
+    Item {
+        name: name.to_string(),
+        worn: false,
+        notes: Vec::new(),
+        contents: Vec::new(),
+    }
+}
+
+fn noted(name: &str, notes: &[&str]) -> Item {
+    Item {
+        notes: notes.iter().map(|note| note.to_string()).collect(),
+        ..item(name)
+    }
+}
+
+#[test]
+fn zork1_nested_inventory() {
+    let output = "You are carrying:\n  \
+                  A brass lantern (providing light)\n  \
+                  A glass bottle\n  \
+                  The glass bottle contains:\n    \
+                  A quantity of water\n  \
+                  A brown sack\n\n>";
+
+    let bottle = Item {
+        contents: vec![item("quantity of water")],
+        ..item("glass bottle")
+    };
+    assert_eq!(
+        parse_inventory(output),
+        Some(vec![
+            noted("brass lantern", &["providing light"]),
+            bottle,
+            item("brown sack"),
+        ])
+    );
+}
+
+#[test]
+fn zork1_empty_handed() {
+    assert_eq!(
+        parse_inventory("You are empty-handed.\n\n>"),
+        Some(Vec::new())
+    );
+}
+
+#[test]
+fn inform_worn_and_lit_items() {
+    let output = "You are carrying:\n  \
+                  a gold watch (being worn)\n  \
+                  a lamp (providing light and being worn)\n  \
+                  a bag (which is open)\n    \
+                  a key\n\n>";
+
+    let watch = Item {
+        worn: true,
+        ..item("gold watch")
+    };
+    let lamp = Item {
+        worn: true,
+        ..noted("lamp", &["providing light"])
+    };
+    let bag = Item {
+        contents: vec![item("key")],
+        ..noted("bag", &["which is open"])
+    };
+    assert_eq!(parse_inventory(output), Some(vec![watch, lamp, bag]));
+}
+
+#[test]
+fn inform_empty_inventory() {
+    assert_eq!(
+        parse_inventory("You are carrying nothing.\n\n>"),
+        Some(Vec::new())
+    );
+}
+
+#[test]
+fn single_line_inventory() {
+    assert_eq!(
+        parse_inventory("You have a lamp, a sword and some coins.\n\n>"),
+        Some(vec![item("lamp"), item("sword"), item("coins")])
+    );
+}
+
+#[test]
+fn other_output_is_not_an_inventory() {
+    assert_eq!(parse_inventory("West of House\n\n>"), None);
+}
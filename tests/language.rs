@@ -0,0 +1,186 @@
+use textplayer::{FailureKind, LanguageProfile};
+
+fn question(output: &str) -> Option<String> {
+    LanguageProfile::english().yes_no_question(output)
+}
+
+#[test]
+fn infocom_affirmative_prompts() {
+    assert_eq!(
+        question("Your score is 10 (total of 350 points), in 34 moves.\nDo you wish to restart? (Y is affirmative): "),
+        Some("Do you wish to restart?".to_string())
+    );
+    assert_eq!(
+        question("Do you wish to leave the game? (Y is affirmative): >"),
+        Some("Do you wish to leave the game?".to_string())
+    );
+}
+
+#[test]
+fn trailing_questions() {
+    assert_eq!(
+        question("Are you sure you want to quit? "),
+        Some("Are you sure you want to quit?".to_string())
+    );
+    assert_eq!(
+        question("The troll looks at you.\nDo you really want to attack\nthe troll? > "),
+        Some("Do you really want to attack the troll?".to_string())
+    );
+    assert_eq!(
+        question("Overwrite existing file? (y/n) "),
+        Some("Overwrite existing file? (y/n)".to_string())
+    );
+}
+
+#[test]
+fn questions_offering_a_choice_are_not_yes_no() {
+    assert_eq!(question("Do you want the red door or the blue door?"), None);
+}
+
+#[test]
+fn question_followed_by_a_prompt_is_part_of_the_story() {
+    assert_eq!(
+        question("The old man asks, \"Do you know the way?\"\n\n>"),
+        None
+    );
+    assert_eq!(
+        question("Do you wish to restart? (Y is affirmative): no\n\n>"),
+        None
+    );
+}
+
+fn classify(language: &LanguageProfile, output: &str) -> Option<FailureKind> {
+    language.failures.as_ref().unwrap().classify(output)
+}
+
+#[test]
+fn german_failures() {
+    let german = LanguageProfile::german();
+    let cases = [
+        (
+            "Welchen Schlüssel meinst du, den goldenen oder den silbernen?",
+            FailureKind::Disambiguation,
+        ),
+        ("Dieses Verb kenne ich nicht.", FailureKind::UnknownVerb),
+        (
+            "Ich kenne das Wort \"xyzzy\" nicht.",
+            FailureKind::UnknownNoun,
+        ),
+        (
+            "So etwas kannst du hier nicht sehen.",
+            FailureKind::NotVisible,
+        ),
+        ("Was willst du nehmen?", FailureKind::Incomplete),
+        ("Das geht nicht.", FailureKind::Impossible),
+    ];
+
+    for (output, kind) in cases {
+        assert_eq!(classify(&german, output), Some(kind), "{:?}", output);
+    }
+    assert_eq!(classify(&german, "Genommen."), None);
+}
+
+#[test]
+fn spanish_failures() {
+    let spanish = LanguageProfile::spanish();
+    let cases = [
+        (
+            "¿Cuál quieres decir, la llave dorada o la llave plateada?",
+            FailureKind::Disambiguation,
+        ),
+        (
+            "Ese no es un verbo que reconozca.",
+            FailureKind::UnknownVerb,
+        ),
+        ("No conozco la palabra \"xyzzy\".", FailureKind::UnknownNoun),
+        ("No puedes ver nada parecido.", FailureKind::NotVisible),
+        ("¿Qué quieres coger?", FailureKind::Incomplete),
+        ("No puedes ir por ahí.", FailureKind::Impossible),
+    ];
+
+    for (output, kind) in cases {
+        assert_eq!(classify(&spanish, output), Some(kind), "{:?}", output);
+    }
+    assert_eq!(classify(&spanish, "Cogido."), None);
+}
+
+#[test]
+fn german_yes_no_questions() {
+    let german = LanguageProfile::german();
+
+    assert_eq!(
+        german.yes_no_question("Willst du wirklich neu starten? "),
+        Some("Willst du wirklich neu starten?".to_string())
+    );
+    assert_eq!(
+        german.yes_no_question("Datei überschreiben? (j/n) >"),
+        Some("Datei überschreiben? (j/n) >".to_string())
+    );
+    assert_eq!(
+        german.yes_no_question("Willst du den roten oder den blauen Knopf?"),
+        None
+    );
+    assert_eq!(german.yes_no_question("Willst du neu starten?\n\n>"), None);
+}
+
+#[test]
+fn spanish_yes_no_questions() {
+    let spanish = LanguageProfile::spanish();
+
+    assert_eq!(
+        spanish.yes_no_question("¿Realmente quieres abandonar el juego? "),
+        Some("¿Realmente quieres abandonar el juego?".to_string())
+    );
+    assert_eq!(
+        spanish.yes_no_question("¿Quieres la puerta roja o la azul?"),
+        None
+    );
+    assert_eq!(spanish.yes_no_question("¿Quieres reiniciar?\n\n>"), None);
+}
+
+#[test]
+fn terminators_follow_the_profile() {
+    let german = LanguageProfile::german();
+    assert!(german
+        .terminators
+        .quit
+        .is_match("Willst du das Spiel wirklich beenden? "));
+    assert!(german
+        .terminators
+        .done
+        .is_match("Speichern fehlgeschlagen."));
+    assert!(german
+        .terminators
+        .save_file
+        .is_match("Datei überschreiben? "));
+    assert!(german
+        .terminators
+        .restart
+        .is_match("Willst du wirklich neu starten? "));
+
+    let spanish = LanguageProfile::spanish();
+    assert!(spanish
+        .terminators
+        .quit
+        .is_match("¿Seguro que quieres terminar? "));
+    assert!(spanish.terminators.done.is_match("Hecho."));
+    assert!(spanish
+        .terminators
+        .save_file
+        .is_match("¿Sobrescribir el fichero? "));
+
+    for language in [german, spanish, LanguageProfile::english()] {
+        assert!(language.terminators.done.is_match("Story text\n\n>"));
+        assert!(!language.terminators.done.is_match("Story text\n"));
+    }
+}
+
+#[test]
+fn update_terminators_picks_up_changed_patterns() {
+    let mut english = LanguageProfile::english();
+    english.quit_confirm = regex::Regex::new(r"Really leave\?").unwrap();
+    assert!(!english.terminators.quit.is_match("Really leave? "));
+
+    english.update_terminators();
+    assert!(english.terminators.quit.is_match("Really leave? "));
+}
@@ -0,0 +1,44 @@
+use textplayer::Normalizer;
+
+#[test]
+fn tidies_case_spacing_and_punctuation() {
+    let normalizer = Normalizer::new();
+
+    assert_eq!(normalizer.normalize("  Take   LAMP!  "), "take lamp");
+    assert_eq!(normalizer.normalize("North?"), "north");
+}
+
+#[test]
+fn expands_abbreviations_for_the_parser() {
+    let scott_adams = Normalizer::for_pack("scott-adams");
+    assert_eq!(scott_adams.normalize("X Lamp"), "examine lamp");
+    assert_eq!(scott_adams.normalize("ne"), "northeast");
+    assert_eq!(scott_adams.normalize("x lamp. n"), "examine lamp. north");
+    assert_eq!(scott_adams.normalize("get x"), "get x");
+
+    let infocom = Normalizer::for_pack("infocom");
+    assert_eq!(infocom.normalize("x lamp"), "examine lamp");
+    assert_eq!(infocom.normalize("l"), "l");
+
+    assert_eq!(
+        Normalizer::for_pack("inform6").normalize("x lamp"),
+        "x lamp"
+    );
+}
+
+#[test]
+fn custom_abbreviations() {
+    let mut normalizer = Normalizer::new();
+    normalizer.add_abbreviation("T", "take");
+
+    assert_eq!(normalizer.normalize("t lamp"), "take lamp");
+}
+
+#[test]
+fn keeps_case_when_asked() {
+    let mut normalizer = Normalizer::new();
+    normalizer.lowercase = false;
+    normalizer.strip_punctuation = false;
+
+    assert_eq!(normalizer.normalize("Say HELLO!"), "Say HELLO!");
+}
@@ -0,0 +1,197 @@
+use std::path::Path;
+use textplayer::{
+    EndingPatterns, FailureKind, FailurePatterns, GameState, Gamefile, StoryMetadata,
+};
+
+fn check(pack: &str, samples: &[(&str, FailureKind)]) {
+    let patterns = FailurePatterns::pack(pack).unwrap();
+    for (line, kind) in samples {
+        assert_eq!(patterns.classify(line), Some(*kind), "{}: {:?}", pack, line);
+    }
+}
+
+#[test]
+fn infocom_failures() {
+    check(
+        "infocom",
+        &[
+            (
+                "Which lamp do you mean, the brass lantern or the broken lantern?",
+                FailureKind::Disambiguation,
+            ),
+            ("I don't know the word \"xyzzy\".", FailureKind::UnknownNoun),
+            ("That's not a verb I recognize.", FailureKind::UnknownVerb),
+            ("You can't see any sword here!", FailureKind::NotVisible),
+            ("What do you want to take?", FailureKind::Incomplete),
+            ("You can't go that way.", FailureKind::Impossible),
+        ],
+    );
+}
+
+#[test]
+fn inform6_failures() {
+    check(
+        "inform6",
+        &[
+            (
+                "Who do you mean, the guard or the thief?",
+                FailureKind::Disambiguation,
+            ),
+            ("That's not a verb I recognise.", FailureKind::UnknownVerb),
+            (
+                "I didn't understand the way that finished.",
+                FailureKind::UnknownNoun,
+            ),
+            ("You can't see any such thing.", FailureKind::NotVisible),
+            (
+                "You'll have to say which compass direction to go in.",
+                FailureKind::Incomplete,
+            ),
+            ("That's hardly portable.", FailureKind::Impossible),
+        ],
+    );
+}
+
+#[test]
+fn inform7_failures() {
+    check(
+        "inform7",
+        &[
+            (
+                "Which do you mean, the red ball or the blue ball?",
+                FailureKind::Disambiguation,
+            ),
+            (
+                "I didn't understand that sentence.",
+                FailureKind::UnknownVerb,
+            ),
+            (
+                "You must name something more substantial.",
+                FailureKind::UnknownNoun,
+            ),
+            ("You can't see any such thing.", FailureKind::NotVisible),
+            ("You must supply a noun.", FailureKind::Incomplete),
+            ("That isn't available.", FailureKind::Impossible),
+        ],
+    );
+}
+
+#[test]
+fn scott_adams_failures() {
+    check(
+        "scott-adams",
+        &[
+            ("You use word(s) I don't know!", FailureKind::UnknownVerb),
+            ("I don't know what a ZORB is.", FailureKind::UnknownNoun),
+            ("I don't see it here.", FailureKind::NotVisible),
+            ("Give me a direction too.", FailureKind::Incomplete),
+            ("I've too much to carry!", FailureKind::Impossible),
+        ],
+    );
+}
+
+#[test]
+fn story_text_is_not_a_failure() {
+    let text = "Taken.";
+    for pack in textplayer::patterns::PACK_NAMES {
+        assert_eq!(FailurePatterns::pack(pack).unwrap().classify(text), None);
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_failure_patterns_are_the_infocom_regexes() {
+    let patterns: &Vec<regex::Regex> = &textplayer::FAILURE_PATTERNS;
+
+    assert_eq!(patterns.len(), textplayer::patterns::INFOCOM_PATTERNS.len());
+    assert!(patterns.iter().any(|p| p.is_match("I beg your pardon?")));
+}
+
+fn story(release: u16, serial: &str, compiler: Option<&str>) -> StoryMetadata {
+    StoryMetadata {
+        version: 5,
+        release,
+        serial: serial.to_string(),
+        checksum: 0,
+        compiler: compiler.map(|c| c.to_string()),
+    }
+}
+
+#[test]
+fn pack_for_story() {
+    let cases = [
+        (story(1, "961111", Some("6.21")), "scott-adams"),
+        (story(88, "840726", None), "infocom"),
+        (story(119, "880429", None), "infocom"),
+        (story(16, "951024", Some("6.10")), "inform6"),
+        (story(1, "070420", Some("6.31")), "inform6"),
+        (story(2, "961111", Some("6.21")), "inform6"),
+        (story(3, "020830", None), "inform6"),
+    ];
+
+    for (metadata, pack) in cases {
+        assert_eq!(
+            FailurePatterns::for_story(&metadata).name(),
+            pack,
+            "{:?}",
+            metadata
+        );
+    }
+}
+
+#[test]
+fn pack_for_bundled_stories() {
+    let games = Path::new(env!("CARGO_MANIFEST_DIR")).join("games");
+    let cases = [
+        ("Adventureland.z5", "scott-adams"),
+        ("zork1.z5", "infocom"),
+        ("curses.z5", "inform6"),
+    ];
+
+    for (file, pack) in cases {
+        let gamefile = Gamefile::new(file.to_string(), games.join(file));
+        let metadata = gamefile.metadata().unwrap();
+        assert_eq!(
+            FailurePatterns::for_story(&metadata).name(),
+            pack,
+            "{}",
+            file
+        );
+    }
+}
+
+#[test]
+fn infocom_endings() {
+    let endings = EndingPatterns::pack("infocom").unwrap();
+
+    let death = "The troll's axe removes your head.\n\n    ****  You have died  ****\n\n\
+                 Now, let's take a look here...";
+    assert_eq!(endings.detect(death), Some(GameState::Dead));
+
+    let menu = "Your score is 10 (total of 350 points), in 34 moves.\n\
+                This gives you the rank of Beginner.\n\n\
+                Would you like to restart the game from the beginning, restore a saved game\n\
+                position, or end this session of the game?\n\
+                (Type RESTART, RESTORE, or QUIT):\n\n>";
+    assert_eq!(endings.detect(menu), Some(GameState::Ended));
+
+    assert_eq!(endings.detect("Taken.\n\n>"), None);
+}
+
+#[test]
+fn inform_endings() {
+    let endings = EndingPatterns::pack("inform6").unwrap();
+
+    let won = "You step into the sunlight.\n\n    *** You have won ***\n\n\
+               In that game you scored 50 out of a possible 50, in 120 turns.\n\n\
+               Would you like to RESTART, RESTORE a saved game, UNDO your last move or QUIT?\n>";
+    assert_eq!(endings.detect(won), Some(GameState::Won));
+    assert_eq!(
+        endings.detect("    *** You have died ***\n"),
+        Some(GameState::Dead)
+    );
+    assert_eq!(
+        endings.detect("Would you like to RESTART, RESTORE a saved game or QUIT?\n>"),
+        Some(GameState::Ended)
+    );
+}
@@ -0,0 +1,93 @@
+use textplayer::reflow::{terminal_width, unwrap, wrap, DFROTZ_WIDTH};
+use textplayer::Reflow;
+
+const WRAPPED: &str = "\
+You are standing in an open field west of a white house, with a boarded front
+door. There is a small mailbox here.";
+
+#[test]
+fn wrapped_paragraph_is_joined() {
+    assert_eq!(
+        unwrap(WRAPPED, DFROTZ_WIDTH),
+        "You are standing in an open field west of a white house, with a boarded front \
+         door. There is a small mailbox here."
+    );
+}
+
+#[test]
+fn paragraphs_stay_separate() {
+    let text = format!("{}\n\n{}", WRAPPED, WRAPPED);
+    let joined = unwrap(&text, DFROTZ_WIDTH);
+
+    assert_eq!(joined.split("\n\n").count(), 2);
+    assert_eq!(joined.lines().count(), 3);
+}
+
+#[test]
+fn short_lines_are_kept() {
+    let text = "West of House\nYou are standing in an open field.";
+    assert_eq!(unwrap(text, DFROTZ_WIDTH), text);
+}
+
+#[test]
+fn indented_lines_and_lists_are_kept() {
+    let text = "\
+You are carrying a great many things, more than you could possibly count here:
+  A brass lantern
+- a leaflet
+2. a sword";
+    assert_eq!(unwrap(text, DFROTZ_WIDTH), text);
+}
+
+#[test]
+fn laid_out_lines_are_kept() {
+    let text = "\
+ West of House                                    Score: 0        Moves: 1
+You are standing in an open field west of a white house, with a boarded front
++-----+";
+    assert_eq!(unwrap(text, DFROTZ_WIDTH), text);
+}
+
+#[test]
+fn wrap_breaks_at_words() {
+    assert_eq!(
+        wrap("There is a small mailbox here.", 12),
+        "There is a\nsmall\nmailbox\nhere."
+    );
+}
+
+#[test]
+fn wrap_keeps_indentation() {
+    assert_eq!(
+        wrap("  A brass lantern and a sword", 16),
+        "  A brass\n  lantern and a\n  sword"
+    );
+}
+
+#[test]
+fn wrap_leaves_short_and_laid_out_lines() {
+    assert_eq!(wrap("Taken.", 10), "Taken.");
+    assert_eq!(wrap("=====================", 10), "=====================");
+}
+
+#[test]
+fn reflow_rewraps_to_its_width() {
+    let reflow = Reflow::new().with_width(Some(40));
+    let text = reflow.apply(WRAPPED);
+
+    assert!(text.lines().all(|line| line.chars().count() <= 40));
+    assert_eq!(unwrap(&text, 40), unwrap(WRAPPED, DFROTZ_WIDTH));
+}
+
+#[test]
+fn reflow_without_width_only_joins() {
+    assert_eq!(Reflow::new().apply(WRAPPED).lines().count(), 1);
+}
+
+#[test]
+fn terminal_width_prefers_columns() {
+    std::env::set_var("COLUMNS", "72");
+    assert_eq!(terminal_width(), Some(72));
+    assert_eq!(Reflow::for_terminal().width, Some(72));
+    std::env::remove_var("COLUMNS");
+}
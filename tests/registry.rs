@@ -0,0 +1,88 @@
+use textplayer::command_result::Operation;
+use textplayer::formatters::{Formatter, FormatterOptions, FormatterRegistry, Formatters};
+use textplayer::{CommandResult, Error};
+
+/// Formatter that writes only the command
+struct InputOnly;
+
+impl Formatter for InputOnly {
+    fn format(&self, result: &CommandResult) -> String {
+        format!("> {}\n", result.input)
+    }
+}
+
+fn look() -> CommandResult {
+    CommandResult::new(
+        "look".to_string(),
+        "West of House\n\n>".to_string(),
+        Operation::Look,
+        true,
+        None,
+    )
+}
+
+#[test]
+fn unknown_formatter_lists_the_names() {
+    match Formatters::by_name("yaml") {
+        Err(Error::UnknownFormatter(name, expected)) => {
+            assert_eq!(name, "yaml");
+            for builtin in [
+                "data", "html", "json", "markdown", "ndjson", "shell", "text",
+            ] {
+                assert!(
+                    expected.contains(builtin),
+                    "{} missing from {}",
+                    builtin,
+                    expected
+                );
+            }
+        }
+        other => panic!(
+            "expected an unknown formatter error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+fn template_formatter_needs_a_template() {
+    assert!(matches!(
+        Formatters::by_name("template").map(|_| ()),
+        Err(Error::MissingTemplate)
+    ));
+}
+
+#[test]
+fn registry_creates_custom_formatters() {
+    let mut registry = FormatterRegistry::empty();
+    assert!(!registry.contains("input-only"));
+
+    registry.register("Input-Only", |_: &FormatterOptions| Ok(Box::new(InputOnly)));
+    assert!(registry.contains("input-only"));
+    assert_eq!(registry.names(), vec!["input-only".to_string()]);
+
+    let formatter = registry
+        .create("INPUT-ONLY", &FormatterOptions::default())
+        .unwrap();
+    assert_eq!(formatter.format(&look()), "> look\n");
+}
+
+#[test]
+fn registering_replaces_a_formatter() {
+    let mut registry = FormatterRegistry::new();
+    registry.register("text", |_: &FormatterOptions| Ok(Box::new(InputOnly)));
+
+    let formatter = registry
+        .create("text", &FormatterOptions::default())
+        .unwrap();
+    assert_eq!(formatter.format(&look()), "> look\n");
+}
+
+#[test]
+fn globally_registered_formatters_are_selectable_by_name() {
+    Formatters::register("input-only", |_: &FormatterOptions| Ok(Box::new(InputOnly)));
+
+    assert!(Formatters::names().contains(&"input-only".to_string()));
+    let formatter = Formatters::by_name("input-only").unwrap();
+    assert_eq!(formatter.format(&look()), "> look\n");
+}
@@ -0,0 +1,69 @@
+use textplayer::room::parse_room;
+
+const KITCHEN: &str = "Kitchen\n\
+You are in the kitchen of the white house. A table seems to have been used\n\
+recently for the preparation of food. A passage leads to the west and a dark\n\
+staircase can be seen leading upward. A dark chimney leads down and to the east\n\
+is a small window which is open.\n\
+On the table is an elongated brown sack, smelling of hot peppers.\n\
+A bottle is sitting on the table.\n\
+The glass bottle contains:\n  \
+A quantity of water\n\n>";
+
+#[test]
+fn zork1_kitchen() {
+    let room = parse_room(KITCHEN).unwrap();
+
+    assert_eq!(room.name, "Kitchen");
+    assert_eq!(
+        room.description,
+        "You are in the kitchen of the white house. A table seems to have been used \
+         recently for the preparation of food. A passage leads to the west and a dark \
+         staircase can be seen leading upward. A dark chimney leads down and to the east \
+         is a small window which is open."
+    );
+    assert_eq!(
+        room.objects,
+        vec!["elongated brown sack", "bottle", "quantity of water"]
+    );
+    assert_eq!(room.exits, vec!["east", "west", "up", "down"]);
+}
+
+#[test]
+fn zork1_west_of_house() {
+    let output = "West of House\n\
+You are standing in an open field west of a white house, with a boarded front\n\
+door.\n\
+There is a small mailbox here.\n\n>";
+
+    let room = parse_room(output).unwrap();
+
+    assert_eq!(room.name, "West of House");
+    assert_eq!(
+        room.description,
+        "You are standing in an open field west of a white house, with a boarded front door."
+    );
+    assert_eq!(room.objects, vec!["small mailbox"]);
+}
+
+#[test]
+fn inform_listing_paragraph() {
+    let output = "Drawing Room\n\
+In the corner is a writing desk. A door leads north.\n\n\
+You can see a brass lamp and an old map here.\n\n>";
+
+    let room = parse_room(output).unwrap();
+
+    assert_eq!(
+        room.description,
+        "In the corner is a writing desk. A door leads north."
+    );
+    assert_eq!(room.objects, vec!["brass lamp", "old map"]);
+    assert_eq!(room.exits, vec!["north"]);
+}
+
+#[test]
+fn sentences_are_not_rooms() {
+    assert_eq!(parse_room("The door is locked.\n\n>"), None);
+    assert_eq!(parse_room("You have died\n\n>"), None);
+}
@@ -0,0 +1,11 @@
+use textplayer::CommandResult;
+
+/// The published schema must be regenerated with `textplayer schema` when
+/// `CommandResult` changes
+#[test]
+fn published_schema_is_current() {
+    let published: serde_json::Value =
+        serde_json::from_str(include_str!("../schema/command_result.schema.json")).unwrap();
+
+    assert_eq!(published, CommandResult::schema());
+}
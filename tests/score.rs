@@ -0,0 +1,177 @@
+use textplayer::score::{parse_score, parse_score_with, Score};
+use textplayer::LanguageProfile;
+
+fn score(score: i32, out_of: Option<i32>, moves: Option<i32>, rank: Option<&str>) -> Score {
+    Score {
+        score,
+        out_of,
+        moves,
+        rank: rank.map(|r| r.to_string()),
+    }
+}
+
+#[test]
+fn infocom_total_of() {
+    let output = "Your score is 10 (total of 350 points), in 34 moves.\n\
+                  This gives you the rank of Beginner.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(10, Some(350), Some(34), Some("Beginner")))
+    );
+}
+
+#[test]
+fn infocom_single_move() {
+    let output = "Your score is 0 (total of 350 points), in 1 move.\n\
+                  This gives you the rank of Beginner.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(0, Some(350), Some(1), Some("Beginner")))
+    );
+}
+
+#[test]
+fn infocom_possible() {
+    let output = "Your score is 25 of a possible 400, in 73 turns.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(25, Some(400), Some(73), None))
+    );
+}
+
+#[test]
+fn infocom_rank_with_article() {
+    let output = "Your score is 310 (total of 350 points), in 402 moves.\n\
+                  This gives you the rank of a Wizard.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(310, Some(350), Some(402), Some("Wizard")))
+    );
+}
+
+#[test]
+fn inform_so_far() {
+    let output = "You have so far scored 10 out of a possible 350, in 34 turns, earning you\n\
+                  the rank of Amateur Adventurer.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(10, Some(350), Some(34), Some("Amateur Adventurer")))
+    );
+}
+
+#[test]
+fn inform_without_rank() {
+    let output = "You have so far scored 0 out of a possible 30, in 2 turns.\n\n>";
+
+    assert_eq!(parse_score(output), Some(score(0, Some(30), Some(2), None)));
+}
+
+#[test]
+fn inform_hard_wrapped() {
+    let output = "You have so far scored 5 out of a possible 100, in\n12 turns.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(5, Some(100), Some(12), None))
+    );
+}
+
+#[test]
+fn turns_before_score() {
+    let output = "In 34 turns you have scored 10 out of 350.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(10, Some(350), Some(34), None))
+    );
+}
+
+#[test]
+fn scott_adams_rating() {
+    let output = "I've stored 2 treasures. On a scale of 0 to 100 that rates a 16.\n\n>";
+
+    assert_eq!(parse_score(output), Some(score(16, Some(100), None, None)));
+}
+
+#[test]
+fn scott_adams_rating_with_comma() {
+    let output = "I've stored 0 treasures.  On a scale of 0 to 100, that rates 0.\n\n>";
+
+    assert_eq!(parse_score(output), Some(score(0, Some(100), None, None)));
+}
+
+#[test]
+fn status_line() {
+    let output = "Score: 10   Moves: 34\n\n>";
+
+    assert_eq!(parse_score(output), Some(score(10, None, Some(34), None)));
+}
+
+#[test]
+fn negative_score() {
+    let output = "Your score is -5 (total of 350 points), in 12 moves.\n\n>";
+
+    assert_eq!(
+        parse_score(output),
+        Some(score(-5, Some(350), Some(12), None))
+    );
+}
+
+#[test]
+fn no_score() {
+    assert_eq!(parse_score("I don't understand that.\n\n>"), None);
+}
+
+#[test]
+fn german() {
+    let output = "Du hast bisher 10 von 100 möglichen Punkten erreicht, in 34 Zügen.\n\n>";
+    let formats = LanguageProfile::german().score_formats;
+
+    assert_eq!(
+        parse_score_with(output, &formats),
+        Some(score(10, Some(100), Some(34), None))
+    );
+}
+
+#[test]
+fn spanish() {
+    let output = "Has conseguido 10 puntos de un total de 100, en 34 turnos.\n\n>";
+    let formats = LanguageProfile::spanish().score_formats;
+
+    assert_eq!(
+        parse_score_with(output, &formats),
+        Some(score(10, Some(100), Some(34), None))
+    );
+}
+
+#[test]
+fn treasure_count_is_not_a_score() {
+    let output = "You have 3 treasures. Your score is 10\n\n>";
+
+    assert_eq!(parse_score(output), Some(score(10, None, None, None)));
+}
+
+#[test]
+fn plain_points() {
+    assert_eq!(
+        parse_score("You have 25 points.\n\n>"),
+        Some(score(25, None, None, None))
+    );
+    assert_eq!(parse_score("You have 3 treasures.\n\n>"), None);
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_score_regex() {
+    let captures = textplayer::SCORE_REGEX
+        .captures("10 (total of 350)")
+        .unwrap();
+
+    assert_eq!(&captures[1], "10");
+    assert_eq!(&captures[2], "350");
+}
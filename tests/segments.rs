@@ -0,0 +1,71 @@
+use textplayer::Segments;
+
+const STATUS: &str = " West of House                                  Score: 0        Moves: 1";
+
+#[test]
+fn splits_echo_status_body_and_prompt() {
+    let output = format!(
+        "> north\n{}\n\nNorth of House\nYou are facing the north side of a white house.\n\n>",
+        STATUS
+    );
+
+    let segments = Segments::split(&output, "north");
+
+    assert_eq!(segments.echo.as_deref(), Some("north"));
+    assert_eq!(segments.status.as_deref(), Some(STATUS.trim()));
+    assert_eq!(
+        segments.body,
+        "North of House\nYou are facing the north side of a white house."
+    );
+    assert_eq!(segments.prompt.as_deref(), Some(">"));
+    assert!(segments.has_prompt());
+}
+
+#[test]
+fn plain_output_is_all_body() {
+    let segments = Segments::split("\nTaken.\n\n\n\nThe lamp is now on.\n", "take lamp");
+
+    assert_eq!(segments.echo, None);
+    assert_eq!(segments.status, None);
+    assert_eq!(segments.body, "Taken.\n\nThe lamp is now on.");
+    assert!(!segments.has_prompt());
+}
+
+#[test]
+fn echo_must_match_the_command() {
+    let segments = Segments::split("North of House\n\n>", "north");
+
+    assert_eq!(segments.echo, None);
+    assert_eq!(segments.body, "North of House");
+}
+
+#[test]
+fn status_line_only_before_the_story() {
+    let output = format!("Taken.\n{}\n\n>", STATUS);
+
+    let segments = Segments::split(&output, "take lamp");
+
+    assert_eq!(segments.status, None);
+    assert!(segments.body.ends_with("Moves: 1"));
+}
+
+#[test]
+fn merged_output_drops_every_prompt() {
+    let output = "Do you wish to restart? (Y is affirmative): \n> y\nRestarting.\n\n>";
+
+    let segments = Segments::split(output, "restart");
+
+    assert_eq!(
+        segments.body,
+        "Do you wish to restart? (Y is affirmative):\ny\nRestarting."
+    );
+    assert_eq!(segments.prompt.as_deref(), Some(">"));
+}
+
+#[test]
+fn prompt_is_only_kept_when_the_output_ends_there() {
+    let segments = Segments::split("Which lamp?\n\n>\nTaken.\n", "take lamp");
+
+    assert_eq!(segments.body, "Which lamp?\n\nTaken.");
+    assert_eq!(segments.prompt, None);
+}
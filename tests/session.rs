@@ -0,0 +1,260 @@
+mod common;
+
+use common::FakeGame;
+use textplayer::command_result::Operation;
+use textplayer::{DisambiguationPolicy, Error, FailureKind, Normalizer, ReadEnd};
+
+const OPENING: &str =
+    "West of House\nYou are standing in an open field west of a white house.\n\n>";
+
+#[test]
+fn control_characters_never_reach_the_interpreter() {
+    let mut game = FakeGame::new(OPENING, &[("look", "West of House\n\n>")]);
+    game.session.start().unwrap();
+
+    for input in [
+        "look\nsave",
+        "look\rquit",
+        "look\u{1b}[A",
+        "\u{0}",
+        "look\t",
+    ] {
+        assert!(
+            matches!(game.session.call(input), Err(Error::ControlCharacter(_))),
+            "{:?} wasn't rejected",
+            input
+        );
+    }
+    assert!(matches!(
+        game.session.disambiguate("lamp\nquit"),
+        Err(Error::ControlCharacter('\n'))
+    ));
+
+    game.session.call("look").unwrap();
+    assert_eq!(game.received(), vec!["look"]);
+}
+
+const WHICH_LAMP: &str = "Which lamp do you mean, the brass lantern or the broken lantern?\n\n>";
+
+#[test]
+fn disambiguation_is_returned_by_default() {
+    let mut game = FakeGame::new(OPENING, &[("take lamp", WHICH_LAMP)]);
+    game.session.start().unwrap();
+
+    let result = game.session.call("take lamp").unwrap();
+
+    assert_eq!(result.failure, Some(FailureKind::Disambiguation));
+    assert_eq!(
+        result.get_detail("candidates"),
+        Some(&serde_json::json!(["brass lantern", "broken lantern"]))
+    );
+    assert_eq!(game.received(), vec!["take lamp"]);
+}
+
+#[test]
+fn first_policy_answers_with_the_first_candidate() {
+    let mut game = FakeGame::new(
+        OPENING,
+        &[("take lamp", WHICH_LAMP), ("brass lantern", "Taken.\n\n>")],
+    );
+    game.session
+        .set_disambiguation_policy(DisambiguationPolicy::First);
+    game.session.start().unwrap();
+
+    let result = game.session.call("take lamp").unwrap();
+
+    assert!(result.success);
+    assert_eq!(result.input, "take lamp");
+    assert_eq!(
+        result.get_detail("chosen"),
+        Some(&serde_json::json!("brass lantern"))
+    );
+    assert!(result.raw_output.contains("Which lamp do you mean"));
+    assert!(result.raw_output.ends_with("Taken.\n\n>"));
+    assert_eq!(game.received(), vec!["take lamp", "brass lantern"]);
+}
+
+#[test]
+fn normalized_commands_keep_the_typed_input() {
+    let mut game = FakeGame::new(
+        OPENING,
+        &[
+            (
+                "look",
+                "West of House\nYou are standing in an open field.\n\n>",
+            ),
+            ("examine mailbox", "The small mailbox is closed.\n\n>"),
+            ("X MAILBOX", "I don't know the word \"x\".\n\n>"),
+        ],
+    );
+    game.session
+        .set_normalizer(Some(Normalizer::for_pack("scott-adams")));
+    game.session.start().unwrap();
+
+    let look = game.session.call("L").unwrap();
+    assert_eq!(look.operation, Operation::Look);
+    assert_eq!(look.input, "L");
+    assert_eq!(look.sent.as_deref(), Some("look"));
+
+    let examine = game.session.call("X Mailbox.").unwrap();
+    assert_eq!(examine.operation, Operation::Action);
+    assert_eq!(examine.input, "X Mailbox.");
+    assert_eq!(examine.sent.as_deref(), Some("examine mailbox"));
+
+    let verbatim = game.session.call("!X MAILBOX").unwrap();
+    assert_eq!(verbatim.input, "!X MAILBOX");
+    assert_eq!(verbatim.sent, None);
+
+    assert_eq!(
+        game.received(),
+        vec!["look", "examine mailbox", "X MAILBOX"]
+    );
+}
+
+#[test]
+fn system_verbs_keep_the_typed_input_without_a_normalizer() {
+    let mut game = FakeGame::new(OPENING, &[("look", "West of House\n\n>")]);
+    game.session.start().unwrap();
+
+    let look = game.session.call("l").unwrap();
+
+    assert_eq!(look.operation, Operation::Look);
+    assert_eq!(look.input, "l");
+    assert_eq!(look.sent, None);
+}
+
+#[test]
+fn restart_succeeds_at_a_fresh_prompt() {
+    let mut game = FakeGame::new(
+        OPENING,
+        &[
+            ("restart", "Do you wish to restart? (Y is affirmative): "),
+            ("y", OPENING),
+        ],
+    );
+    game.session.start().unwrap();
+
+    let result = game.session.call("restart").unwrap();
+
+    assert_eq!(result.operation, Operation::Restart);
+    assert!(result.success);
+    assert_eq!(game.received(), vec!["restart", "y"]);
+}
+
+#[test]
+fn restart_refused_by_the_game_is_a_failure() {
+    let mut game = FakeGame::new(OPENING, &[]);
+    game.session.start().unwrap();
+
+    let result = game.session.call("restart").unwrap();
+
+    assert_eq!(result.operation, Operation::Restart);
+    assert!(!result.success);
+    assert_eq!(result.message.as_deref(), Some("Restart failed"));
+}
+
+#[test]
+fn hints_are_revealed_one_at_a_time() {
+    let mut game = FakeGame::new(OPENING, &[]);
+    game.session.start().unwrap();
+
+    let first = game.session.call("hint troll").unwrap();
+    assert_eq!(first.operation, Operation::Hint);
+    assert!(first.success);
+    assert_eq!(
+        first.message.as_deref(),
+        Some("The troll won't let you pass while he is conscious.")
+    );
+    assert_eq!(first.get_detail("remaining"), Some(&serde_json::json!(2)));
+
+    let second = game.session.call("HINT troll").unwrap();
+    assert_eq!(
+        second
+            .get_detail("hints")
+            .and_then(|hints| hints.as_array())
+            .map(Vec::len),
+        Some(2)
+    );
+
+    let topics = game.session.call("hint").unwrap();
+    assert_eq!(
+        topics.get_detail("topics"),
+        Some(&serde_json::json!([
+            "What do I do with the leaflet?",
+            "Where do the treasures go?"
+        ]))
+    );
+
+    assert!(game.received().is_empty());
+}
+
+#[test]
+fn hint_request_without_a_match() {
+    let mut game = FakeGame::new(OPENING, &[]);
+    game.session.start().unwrap();
+
+    let result = game.session.call("hint grue").unwrap();
+
+    assert_eq!(result.operation, Operation::Hint);
+    assert!(!result.success);
+    assert_eq!(
+        result.message.as_deref(),
+        Some("No hint topic matches 'grue'")
+    );
+    assert!(game.received().is_empty());
+}
+
+#[test]
+fn read_end_tells_the_prompt_from_other_patterns() {
+    let mut game = FakeGame::new(
+        OPENING,
+        &[
+            ("wait", "Time passes.\n\n>"),
+            ("sing", "You sing a little tune."),
+        ],
+    );
+    game.session.start().unwrap();
+
+    assert_eq!(
+        game.session.call("wait").unwrap().read_end,
+        Some(ReadEnd::Prompt)
+    );
+
+    let sing = game.session.call("sing").unwrap();
+    assert_eq!(sing.read_end, Some(ReadEnd::Timeout));
+    assert!(sing.is_timeout());
+}
+
+#[test]
+fn an_earlier_timeout_is_kept_over_a_later_match() {
+    let mut game = FakeGame::new(
+        OPENING,
+        &[
+            ("restore", "Hmm.\n"),
+            ("saves/zork1_autosave.qzl", "Ok.\n\n>"),
+        ],
+    );
+    game.session.start().unwrap();
+
+    let result = game.session.call("restore").unwrap();
+
+    assert_eq!(game.received(), vec!["restore", "saves/zork1_autosave.qzl"]);
+    assert_eq!(result.read_end, Some(ReadEnd::Timeout));
+}
+
+#[test]
+fn a_question_then_a_prompt_ends_at_the_prompt() {
+    let mut game = FakeGame::new(
+        OPENING,
+        &[
+            ("restart", "Do you wish to restart? (Y is affirmative): "),
+            ("y", OPENING),
+        ],
+    );
+    game.session.start().unwrap();
+
+    assert_eq!(
+        game.session.call("restart").unwrap().read_end,
+        Some(ReadEnd::Prompt)
+    );
+}
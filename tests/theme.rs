@@ -0,0 +1,55 @@
+use std::ffi::OsStr;
+use textplayer::theme::{colors_allowed, THEME_NAMES};
+use textplayer::{Error, Theme};
+
+#[test]
+fn builtin_themes_by_name() {
+    for name in THEME_NAMES {
+        assert!(Theme::by_name(name).is_ok(), "{}", name);
+    }
+    assert_eq!(Theme::by_name("dark").unwrap(), Theme::dark());
+    assert_eq!(Theme::by_name("plain").unwrap(), Theme::plain());
+}
+
+#[test]
+fn unknown_theme_lists_the_names() {
+    match Theme::by_name("solarized") {
+        Err(Error::UnknownTheme(name, expected)) => {
+            assert_eq!(name, "solarized");
+            assert_eq!(expected, "dark, light, plain");
+        }
+        other => panic!("expected an unknown theme error, got {:?}", other),
+    }
+}
+
+#[test]
+fn paint_wraps_text_in_escape_codes() {
+    assert_eq!(
+        Theme::paint("1;33", "West of House"),
+        "\x1b[1;33mWest of House\x1b[0m"
+    );
+    assert_eq!(Theme::paint("", "West of House"), "West of House");
+    assert_eq!(Theme::paint("32", ""), "");
+}
+
+#[test]
+fn colors_need_a_terminal() {
+    assert!(colors_allowed(None, true));
+    assert!(!colors_allowed(None, false));
+}
+
+#[test]
+fn no_color_disables_colors() {
+    assert!(!colors_allowed(Some(OsStr::new("1")), true));
+    assert!(colors_allowed(Some(OsStr::new("")), true));
+}
+
+#[test]
+fn no_color_falls_back_to_plain() {
+    // The only test touching the environment, so no other test sees NO_COLOR
+    std::env::set_var("NO_COLOR", "1");
+    assert_eq!(Theme::for_stdout(), Theme::plain());
+    assert_eq!(Theme::light().if_supported(), Theme::plain());
+    assert_eq!(Theme::default(), Theme::plain());
+    std::env::remove_var("NO_COLOR");
+}
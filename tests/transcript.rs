@@ -0,0 +1,100 @@
+mod common;
+
+use common::FakeGame;
+use std::collections::BTreeMap;
+use textplayer::command_result::Operation;
+use textplayer::transcript::TRANSCRIPT_VERSION;
+use textplayer::{CommandResult, Error, Transcript, TranscriptHeader};
+
+fn transcript() -> Transcript {
+    let mut options = BTreeMap::new();
+    options.insert("language".to_string(), "en".to_string());
+    let header = TranscriptHeader {
+        game: "zork1".to_string(),
+        story: common::zork1().metadata().ok(),
+        interpreter: Some("FROTZ V2.54".to_string()),
+        seed: Some(42),
+        options,
+    };
+    let mut result = CommandResult::new(
+        "north".to_string(),
+        "North of House\n\n>".to_string(),
+        Operation::Action,
+        true,
+        None,
+    );
+    result.turn = 1;
+
+    Transcript::new(header, vec![result])
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("zork1.transcript.json");
+    let transcript = transcript();
+
+    transcript.save(&path).unwrap();
+    let loaded = Transcript::load(&path).unwrap();
+
+    assert_eq!(loaded.version, TRANSCRIPT_VERSION);
+    assert_eq!(loaded.header, transcript.header);
+    assert_eq!(
+        serde_json::to_value(&loaded.results).unwrap(),
+        serde_json::to_value(&transcript.results).unwrap()
+    );
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("future.json");
+    let mut json = serde_json::to_value(transcript()).unwrap();
+    json["version"] = serde_json::json!(TRANSCRIPT_VERSION + 1);
+    std::fs::write(&path, json.to_string()).unwrap();
+
+    assert!(matches!(
+        Transcript::load(&path),
+        Err(Error::UnsupportedTranscriptVersion(version, TRANSCRIPT_VERSION))
+            if version == TRANSCRIPT_VERSION + 1
+    ));
+}
+
+#[test]
+fn malformed_transcripts_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("broken.json");
+    std::fs::write(&path, "{\"version\": 1}").unwrap();
+
+    assert!(matches!(
+        Transcript::load(&path),
+        Err(Error::InvalidTranscript(..))
+    ));
+}
+
+#[test]
+fn session_transcript_asks_the_interpreter_version_once() {
+    let mut game = FakeGame::new("West of House\n\n>", &[("wait", "Time passes.\n\n>")]);
+    game.session.record_transcript(true);
+    game.session.start().unwrap();
+    game.session.call("wait").unwrap();
+
+    let first = game.session.transcript();
+    let second = game.session.take_transcript();
+
+    assert_eq!(
+        first.header.interpreter.as_deref(),
+        Some("FROTZ V2.54 (fake)")
+    );
+    assert_eq!(second.header, first.header);
+    assert_eq!(
+        second
+            .results
+            .iter()
+            .map(|r| r.input.as_str())
+            .collect::<Vec<_>>(),
+        vec!["", "wait"]
+    );
+    assert!(game.session.take_transcript().results.is_empty());
+    assert_eq!(game.version_queries(), 1);
+}